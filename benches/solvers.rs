@@ -0,0 +1,192 @@
+//! Times every registered puzzle on its example, the personal input (when
+//! there is one) and synthetic large inputs. Alternative implementations of
+//! the same part are run on the same inputs and reported side by side.
+//!
+//! `cargo bench -- "day 9"` only runs day 9's puzzles, and
+//! `cargo bench -- "day 9 part 2"` only its second part.
+
+use advent_2020::generate::{generate, Rng};
+use advent_2020::input::Inputs;
+use advent_2020::{Args, Puzzle, PUZZLES};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Roughly how long to spend timing each implementation on each input.
+const BUDGET: Duration = Duration::from_millis(500);
+const MAX_SAMPLES: usize = 50;
+/// Each sample runs the solver enough times to take at least this long, so
+/// that very fast solvers aren't swamped by timer overhead.
+const MIN_SAMPLE_TIME: Duration = Duration::from_millis(2);
+
+struct Case {
+  label: String,
+  text: String,
+  args: Args,
+}
+
+fn cases(puzzle: &Puzzle, inputs: &Inputs) -> Vec<Case> {
+  let mut cases = Vec::new();
+  if let Some(example) = puzzle.example {
+    cases.push(Case {
+      label: "example".to_string(),
+      text: example.text.to_string(),
+      args: example.args(),
+    });
+  }
+  if let Ok(text) = inputs.load(puzzle.day) {
+    cases.push(Case {
+      label: "personal".to_string(),
+      text,
+      args: Args::new(),
+    });
+  }
+  cases.extend(synthetic(puzzle));
+  cases
+}
+
+/// How many lines or records to generate for each day's synthetic input.
+/// Generators cap this where a day's answers would overflow.
+const SYNTHETIC_SIZE: usize = 10_000;
+
+fn synthetic(puzzle: &Puzzle) -> Vec<Case> {
+  let mut cases = Vec::new();
+  if let Some(generated) = generate(puzzle.day, SYNTHETIC_SIZE, 0x2020) {
+    cases.push(Case {
+      label: format!("{} generated lines", generated.text.lines().count()),
+      text: generated.text,
+      args: generated.args,
+    });
+  }
+  if puzzle.day == 3 {
+    // Tall enough that how the map is stored shows up.
+    let generated = generate(3, 100_000, 0x2020).unwrap();
+    cases.push(Case {
+      label: "100k rows".to_string(),
+      text: generated.text,
+      args: generated.args,
+    });
+  }
+  if (puzzle.day, puzzle.part) == (9, 2) {
+    // Generated XMAS data has to stay short to avoid overflow, but part 2
+    // works with any positive numbers, given a target that some run sums to.
+    let mut rng = Rng::new(0x2020);
+    let nums: Vec<i64> = (0..100_000).map(|_| rng.range(1, 1000)).collect();
+    let target: i64 = nums[90_000..90_050].iter().sum();
+    let text: Vec<_> = nums.iter().map(|n| n.to_string()).collect();
+    cases.push(Case {
+      label: "100k numbers".to_string(),
+      text: text.join("\n"),
+      args: Args::new().with("target", &target.to_string()),
+    });
+  }
+  cases
+}
+
+struct Stats {
+  samples: Vec<Duration>,
+}
+impl Stats {
+  fn measure(mut run: impl FnMut()) -> Self {
+    let start = Instant::now();
+    run();
+    let once = start.elapsed();
+    let iterations = if once >= MIN_SAMPLE_TIME {
+      1
+    } else {
+      (MIN_SAMPLE_TIME.as_nanos() / once.as_nanos().max(1)) as u32 + 1
+    };
+
+    let mut samples = Vec::new();
+    let start = Instant::now();
+    while samples.len() < MAX_SAMPLES && (samples.is_empty() || start.elapsed() < BUDGET) {
+      let sample_start = Instant::now();
+      for _ in 0..iterations {
+        run();
+      }
+      samples.push(sample_start.elapsed() / iterations);
+    }
+    samples.sort();
+    Self { samples }
+  }
+
+  fn min(&self) -> Duration {
+    self.samples[0]
+  }
+
+  fn median(&self) -> Duration {
+    self.samples[self.samples.len() / 2]
+  }
+
+  fn max(&self) -> Duration {
+    self.samples[self.samples.len() - 1]
+  }
+}
+
+fn bench_group(implementations: &[&Puzzle], inputs: &Inputs) {
+  let first = implementations[0];
+  for case in cases(first, inputs) {
+    println!("day {} part {} / {}", first.day, first.part, case.label);
+    let mut baseline = None;
+    for puzzle in implementations {
+      if let Err(e) = puzzle.run(&case.text, &case.args) {
+        println!("  {:<16} failed: {}", puzzle.name, e);
+        continue;
+      }
+      let stats = Stats::measure(|| {
+        black_box(puzzle.run(black_box(&case.text), &case.args)).ok();
+      });
+      let comparison = match baseline {
+        None => {
+          baseline = Some(stats.median());
+          String::new()
+        }
+        Some(baseline) => {
+          let ratio = baseline.as_secs_f64() / stats.median().as_secs_f64();
+          if ratio >= 1.0 {
+            format!("  {:.2}x faster", ratio)
+          } else {
+            format!("  {:.2}x slower", 1.0 / ratio)
+          }
+        }
+      };
+      println!(
+        "  {:<16} time: [{:.2?} {:.2?} {:.2?}] ({} samples){}",
+        puzzle.name,
+        stats.min(),
+        stats.median(),
+        stats.max(),
+        stats.samples.len(),
+        comparison
+      );
+    }
+  }
+}
+
+/// Whether `filter` is the whole of `name` or the words it starts with, so
+/// that "day 1" doesn't pick out day 10.
+fn selects(filter: &str, name: &str) -> bool {
+  name
+    .strip_prefix(filter.trim())
+    .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+fn main() {
+  let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+  let inputs = Inputs::from_env();
+  let mut group: Vec<&Puzzle> = Vec::new();
+  for puzzle in PUZZLES.iter() {
+    if let Some(first) = group.first() {
+      if (first.day, first.part) != (puzzle.day, puzzle.part) {
+        bench_group(&group, &inputs);
+        group.clear();
+      }
+    }
+    let name = format!("day {} part {}", puzzle.day, puzzle.part);
+    if filter.as_ref().is_none_or(|f| selects(f, &name)) {
+      group.push(puzzle);
+    }
+  }
+  if !group.is_empty() {
+    bench_group(&group, &inputs);
+  }
+}
@@ -0,0 +1,211 @@
+use advent_2020::generate::generate;
+use advent_2020::input::Inputs;
+use advent_2020::{find, implementations, Args, Puzzle, PUZZLES};
+use std::io::{IsTerminal, Read};
+use std::process::exit;
+use std::time::Instant;
+
+const USAGE: &str = "\
+usage:
+  advent-2020 run --day N --part N [--input PATH] [--impl NAME] [--PARAM VALUE ...]
+  advent-2020 list
+  advent-2020 generate --day N [--size N] [--seed N]
+
+Reads the puzzle input from PATH, or from stdin if PATH is `-`. Without
+--input, reads stdin if something is piped into it, and otherwise looks
+for day_N_input.txt in $ADVENT_2020_INPUTS, ./inputs, and the crate's src/
+directory.
+
+Some parts have alternative implementations, chosen with --impl, and some
+days take extra parameters, like day 9's --window-size and --target;
+`list` shows them.
+
+`generate` prints a random input for a day, with about N lines or records
+(1000 by default), and reports the answers it should have on stderr.";
+
+struct RunOptions {
+  day: u8,
+  part: u8,
+  input: Option<String>,
+  implementation: Option<String>,
+  args: Args,
+}
+impl RunOptions {
+  fn parse(flags: &[String]) -> Result<Self, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut implementation = None;
+    let mut args = Args::new();
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+      let name = match flag.strip_prefix("--") {
+        Some(name) => name,
+        None => return Err(format!("unexpected argument: {}", flag)),
+      };
+      let value = match flags.next() {
+        Some(value) => value,
+        None => return Err(format!("--{} needs a value", name)),
+      };
+      match name {
+        "day" => day = Some(parse_number(name, value)?),
+        "part" => part = Some(parse_number(name, value)?),
+        "input" => input = Some(value.clone()),
+        "impl" => implementation = Some(value.clone()),
+        _ => args.set(name, value),
+      }
+    }
+    Ok(Self {
+      day: day.ok_or("--day is required")?,
+      part: part.ok_or("--part is required")?,
+      input,
+      implementation,
+      args,
+    })
+  }
+}
+
+fn parse_number(name: &str, value: &str) -> Result<u8, String> {
+  value
+    .parse()
+    .map_err(|_| format!("--{} must be a number, got {:?}", name, value))
+}
+
+fn read_input(day: u8, path: Option<&str>) -> Result<String, String> {
+  match path {
+    None => {
+      // Piped input wins over the personal one, unless there's none, as when
+      // stdin is /dev/null.
+      if !std::io::stdin().is_terminal() {
+        let text = read_stdin()?;
+        if !text.is_empty() {
+          return Ok(text);
+        }
+      }
+      Inputs::from_env()
+        .load(day)
+        .map_err(|e| format!("couldn't find an input for day {}: {}", day, e))
+    }
+    Some("-") => read_stdin(),
+    Some(path) => {
+      std::fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))
+    }
+  }
+}
+
+fn read_stdin() -> Result<String, String> {
+  let mut text = String::new();
+  std::io::stdin()
+    .read_to_string(&mut text)
+    .map_err(|e| format!("couldn't read stdin: {}", e))?;
+  Ok(text)
+}
+
+fn run(flags: &[String]) -> Result<(), String> {
+  let options = RunOptions::parse(flags)?;
+  let puzzle = match &options.implementation {
+    None => find(options.day, options.part),
+    Some(name) => implementations(options.day, options.part).find(|p| p.name == name),
+  };
+  let puzzle = puzzle.ok_or_else(|| {
+    format!(
+      "no {} solution for day {} part {}",
+      options.implementation.as_deref().unwrap_or("default"),
+      options.day,
+      options.part
+    )
+  })?;
+  check_params(puzzle, &options.args)?;
+  let text = read_input(options.day, options.input.as_deref())?;
+
+  let start = Instant::now();
+  let answer = puzzle.run(&text, &options.args)?;
+  let elapsed = start.elapsed();
+  println!("{}", answer);
+  eprintln!("day {} part {} took {:?}", puzzle.day, puzzle.part, elapsed);
+  Ok(())
+}
+
+fn check_params(puzzle: &Puzzle, args: &Args) -> Result<(), String> {
+  for name in args.names() {
+    if !puzzle.params.contains(&name) {
+      return Err(format!(
+        "day {} part {} doesn't take --{}",
+        puzzle.day, puzzle.part, name
+      ));
+    }
+  }
+  Ok(())
+}
+
+fn generate_input(flags: &[String]) -> Result<(), String> {
+  let mut day = None;
+  let mut size = 1000;
+  let mut seed = 0;
+  let mut flags = flags.iter();
+  while let Some(flag) = flags.next() {
+    let value = flags
+      .next()
+      .ok_or_else(|| format!("{} needs a value", flag))?;
+    let number = |name| {
+      value
+        .parse()
+        .map_err(|_| format!("--{} must be a number, got {:?}", name, value))
+    };
+    match flag.as_str() {
+      "--day" => day = Some(parse_number("day", value)?),
+      "--size" => size = number("size")? as usize,
+      "--seed" => seed = number("seed")?,
+      _ => return Err(format!("unexpected argument: {}", flag)),
+    }
+  }
+  let day = day.ok_or("--day is required")?;
+  let generated = generate(day, size, seed).ok_or_else(|| format!("can't generate day {}", day))?;
+  print!("{}", generated.text);
+  for name in generated.args.names() {
+    let value: Option<String> = generated.args.get(name)?;
+    eprintln!("run with --{} {}", name, value.unwrap_or_default());
+  }
+  for (part, answer) in generated.answers.iter().enumerate() {
+    eprintln!("part {}: {}", part + 1, answer);
+  }
+  Ok(())
+}
+
+fn list() {
+  for puzzle in PUZZLES {
+    let params: Vec<_> = puzzle
+      .params
+      .iter()
+      .map(|p| format!(" [--{} VALUE]", p))
+      .collect();
+    let implementation = match puzzle.name {
+      "default" => String::new(),
+      name => format!(" --impl {:?}", name),
+    };
+    println!(
+      "day {} part {}{}{}",
+      puzzle.day,
+      puzzle.part,
+      implementation,
+      params.join("")
+    );
+  }
+}
+
+fn main() {
+  let argv: Vec<String> = std::env::args().skip(1).collect();
+  let result = match argv.first().map(|s| s.as_str()) {
+    Some("run") => run(&argv[1..]),
+    Some("generate") => generate_input(&argv[1..]),
+    Some("list") => {
+      list();
+      Ok(())
+    }
+    _ => Err(USAGE.to_string()),
+  };
+  if let Err(message) = result {
+    eprintln!("{}", message);
+    exit(1);
+  }
+}
@@ -1,28 +1,314 @@
-fn multiply_those_that_sum_to(list: &Vec<i64>, target: i64) -> Option<i64> {
-  let mut compliments = std::collections::HashSet::with_capacity(list.len());
-  for k in list {
-    let compliment = target - k;
-    compliments.insert(compliment);
-    if compliments.contains(&k) {
-      return Some(k * compliment);
+use crate::parse::{self, ParseError, ReadError};
+use crate::{Example, Solution};
+use std::collections::hash_map::{Entry, HashMap};
+use std::io::BufRead;
+
+/// Entries of an expense report that sum to some target, in the order they
+/// appear in the report.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Match {
+  /// Each entry's position in the report, counting from 0.
+  pub indices: Vec<usize>,
+  pub values: Vec<i64>,
+}
+impl Match {
+  pub fn product(&self) -> i64 {
+    self.values.iter().product()
+  }
+}
+
+/// Finds two entries at different positions in `list` that sum to `target`,
+/// in a single pass. Unlike `find_sum`, this doesn't need to sort the list.
+pub fn find_pair(list: &[i64], target: i64) -> Option<Match> {
+  let mut seen = HashMap::with_capacity(list.len());
+  for (idx, value) in list.iter().copied().enumerate() {
+    if let Some(&other) = seen.get(&(target - value)) {
+      return Some(Match {
+        indices: vec![other, idx],
+        values: vec![target - value, value],
+      });
     }
+    seen.entry(value).or_insert(idx);
   }
   None
 }
 
-pub fn problem(input: &str) -> Option<i64> {
-  let list = &input.trim().lines().map(|s| s.parse().unwrap()).collect();
-  multiply_those_that_sum_to(list, 2020)
+/// Counts the pairs of entries at different positions in `list` that sum to
+/// `target`. Like `all_sums`, repeated values are counted as separate
+/// entries, but without listing every pair.
+pub fn count_pairs(list: &[i64], target: i64) -> usize {
+  let mut seen: HashMap<i64, usize> = HashMap::with_capacity(list.len());
+  let mut pairs = 0;
+  for value in list.iter().copied() {
+    pairs += seen.get(&(target - value)).copied().unwrap_or(0);
+    *seen.entry(value).or_insert(0) += 1;
+  }
+  pairs
+}
+
+/// Finds `k` entries at different positions in `list` that sum to `target`.
+pub fn find_sum(list: &[i64], k: usize, target: i64) -> Option<Match> {
+  KSum::run(list, k, target, true).pop()
+}
+
+/// Finds every set of `k` entries at different positions in `list` that sum
+/// to `target`. Repeated values are different entries, so `[1010, 1010,
+/// 1010]` has three pairs summing to 2020.
+pub fn all_sums(list: &[i64], k: usize, target: i64) -> Vec<Match> {
+  let mut matches = KSum::run(list, k, target, false);
+  matches.sort_by(|a, b| a.indices.cmp(&b.indices));
+  matches
+}
+
+/// Sorts the entries, then fixes the smallest remaining entry until only two
+/// are left to find, which a pointer from either end of the sorted entries
+/// finds in linear time. That's O(n^(k-1)) overall, after the sort.
+struct KSum {
+  first_only: bool,
+  chosen: Vec<(i64, usize)>,
+  found: Vec<Match>,
 }
+impl KSum {
+  fn run(list: &[i64], k: usize, target: i64, first_only: bool) -> Vec<Match> {
+    let mut sorted: Vec<(i64, usize)> = list.iter().copied().zip(0..).collect();
+    sorted.sort_unstable();
+    let mut search = Self {
+      first_only,
+      chosen: Vec::with_capacity(k),
+      found: Vec::new(),
+    };
+    search.search(&sorted, k, target);
+    search.found
+  }
+
+  fn done(&self) -> bool {
+    self.first_only && !self.found.is_empty()
+  }
 
-pub fn problem_part_2(input: &str) -> Option<i64> {
-  let list: Vec<i64> = input.trim().lines().map(|s| s.parse().unwrap()).collect();
-  for a in list.iter() {
-    if let Some(result) = multiply_those_that_sum_to(&list, 2020 - a) {
-      return Some(a * result);
+  fn search(&mut self, sorted: &[(i64, usize)], k: usize, target: i64) {
+    if k > sorted.len() {
+      return;
+    }
+    let smallest: i64 = sorted[..k].iter().map(|(v, _)| v).sum();
+    let largest: i64 = sorted[sorted.len() - k..].iter().map(|(v, _)| v).sum();
+    if target < smallest || target > largest {
+      return;
+    }
+    match k {
+      0 => self.record(&[]),
+      1 => {
+        for entry in sorted.iter().filter(|(v, _)| *v == target) {
+          self.record(&[*entry]);
+          if self.done() {
+            return;
+          }
+        }
+      }
+      2 => self.pairs(sorted, target),
+      _ => {
+        for (idx, entry) in sorted.iter().enumerate() {
+          self.chosen.push(*entry);
+          self.search(&sorted[idx + 1..], k - 1, target - entry.0);
+          self.chosen.pop();
+          if self.done() {
+            return;
+          }
+        }
+      }
     }
   }
-  None
+
+  fn pairs(&mut self, sorted: &[(i64, usize)], target: i64) {
+    let (mut lo, mut hi) = (0, sorted.len() - 1);
+    while lo < hi {
+      let (low, high) = (sorted[lo].0, sorted[hi].0);
+      match (low + high).cmp(&target) {
+        std::cmp::Ordering::Less => lo += 1,
+        std::cmp::Ordering::Greater => hi -= 1,
+        std::cmp::Ordering::Equal => {
+          // Every entry with the low value pairs with every entry with the
+          // high value, unless they're the same value, when every entry in
+          // between pairs with every other.
+          let lows = sorted[lo..=hi]
+            .iter()
+            .take_while(|(v, _)| *v == low)
+            .count();
+          let highs = sorted[lo..=hi]
+            .iter()
+            .rev()
+            .take_while(|(v, _)| *v == high)
+            .count();
+          for i in lo..lo + lows {
+            let partners = if low == high { i + 1 } else { hi + 1 - highs };
+            for j in partners..=hi {
+              self.record(&[sorted[i], sorted[j]]);
+              if self.done() {
+                return;
+              }
+            }
+          }
+          if low == high {
+            return;
+          }
+          lo += lows;
+          hi -= highs;
+        }
+      }
+    }
+  }
+
+  fn record(&mut self, rest: &[(i64, usize)]) {
+    let mut entries: Vec<(i64, usize)> = self.chosen.iter().chain(rest).copied().collect();
+    entries.sort_unstable_by_key(|(_, idx)| *idx);
+    self.found.push(Match {
+      indices: entries.iter().map(|(_, idx)| *idx).collect(),
+      values: entries.iter().map(|(v, _)| *v).collect(),
+    });
+  }
+}
+
+/// What a streaming search found, and how much of the input it read to find
+/// it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Streamed {
+  pub found: Option<Match>,
+  /// Lines read, including blank ones. When something was found, this is
+  /// the line it was completed on.
+  pub lines_read: usize,
+}
+
+/// Like `find_pair`, but reads entries from `reader` one line at a time and
+/// stops at the first pair. Only the first position of each value is kept,
+/// so memory grows with the number of different values read, not with the
+/// length of the report or the size of `target`.
+pub fn stream_pair(reader: impl BufRead, target: i64) -> Result<Streamed, ReadError> {
+  let mut seen: HashMap<i64, usize> = HashMap::new();
+  stream(reader, |idx, value| {
+    let found = target.checked_sub(value).and_then(|other| {
+      seen.get(&other).map(|&other_idx| Match {
+        indices: vec![other_idx, idx],
+        values: vec![other, value],
+      })
+    });
+    seen.entry(value).or_insert(idx);
+    found
+  })
+}
+
+/// Like `find_sum` with `k` of 3, but reads entries from `reader` one line at
+/// a time and stops at the first triple. Like `stream_pair`, memory grows
+/// with the number of different values read: the first two positions of
+/// each are kept, which is enough to find any pair among them.
+pub fn stream_triple(reader: impl BufRead, target: i64) -> Result<Streamed, ReadError> {
+  let mut values: Vec<i64> = Vec::new();
+  let mut first: HashMap<i64, usize> = HashMap::new();
+  let mut second: HashMap<i64, usize> = HashMap::new();
+  stream(reader, |idx, value| {
+    let found = target.checked_sub(value).and_then(|rest| {
+      // The earliest seen value that's part of a pair summing to `rest`.
+      values.iter().find_map(|&a| {
+        let b = rest.checked_sub(a)?;
+        let b_idx = if a == b {
+          second.get(&b)
+        } else {
+          first.get(&b)
+        }?;
+        let mut entries = [(first[&a], a), (*b_idx, b), (idx, value)];
+        entries.sort_unstable_by_key(|(idx, _)| *idx);
+        Some(Match {
+          indices: entries.iter().map(|(idx, _)| *idx).collect(),
+          values: entries.iter().map(|(_, value)| *value).collect(),
+        })
+      })
+    });
+    match first.entry(value) {
+      Entry::Occupied(_) => {
+        second.entry(value).or_insert(idx);
+      }
+      Entry::Vacant(entry) => {
+        entry.insert(idx);
+        values.push(value);
+      }
+    }
+    found
+  })
+}
+
+/// Feeds each entry to `found`, along with its position among all the
+/// entries, until it finds something.
+fn stream(
+  mut reader: impl BufRead,
+  mut found: impl FnMut(usize, i64) -> Option<Match>,
+) -> Result<Streamed, ReadError> {
+  let mut line = String::new();
+  let mut lines_read = 0;
+  let mut idx = 0;
+  loop {
+    line.clear();
+    if reader.read_line(&mut line)? == 0 {
+      return Ok(Streamed {
+        found: None,
+        lines_read,
+      });
+    }
+    lines_read += 1;
+    if line.trim().is_empty() {
+      continue;
+    }
+    let value: i64 = parse::number(&line).map_err(|e| e.offset(lines_read - 1, 0))?;
+    if let Some(found) = found(idx, value) {
+      return Ok(Streamed {
+        found: Some(found),
+        lines_read,
+      });
+    }
+    idx += 1;
+  }
+}
+
+pub fn problem(input: &str) -> Result<Option<i64>, ParseError> {
+  let list = parse::lines(input, parse::number)?;
+  Ok(find_pair(&list, 2020).map(|m| m.product()))
+}
+
+pub fn problem_part_2(input: &str) -> Result<Option<i64>, ParseError> {
+  let list: Vec<i64> = parse::lines(input, parse::number)?;
+  Ok(find_sum(&list, 3, 2020).map(|m| m.product()))
+}
+
+pub const EXAMPLE: &str = "\
+1721
+979
+366
+299
+675
+1456
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = Option<i64>;
+  const DAY: u8 = 1;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<i64>, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = Option<i64>;
+  const DAY: u8 = 1;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<i64>, ParseError> {
+    problem_part_2(text)
+  }
 }
 
 #[cfg(test)]
@@ -31,31 +317,156 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(Some(514579), problem(EXAMPLE));
+    assert_eq!(Ok(Some(514579)), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(Some(211899), problem(MY_INPUT));
+    let input = my_input!(1);
+    assert_eq!(Ok(Some(211899)), problem(&input));
   }
 
   #[test]
   fn part_2_examples() {
-    assert_eq!(Some(241861950), problem_part_2(EXAMPLE));
+    assert_eq!(Ok(Some(241861950)), problem_part_2(EXAMPLE));
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(Some(275765682), problem_part_2(MY_INPUT));
+    let input = my_input!(1);
+    assert_eq!(Ok(Some(275765682)), problem_part_2(&input));
   }
 
-  static EXAMPLE: &'static str = "\
-1721
-979
-366
-299
-675
-1456
-  ";
-  static MY_INPUT: &'static str = include_str!("day_1_input.txt");
+  #[test]
+  fn pairs() {
+    assert_eq!(None, find_pair(&[1010], 2020));
+    assert_eq!(None, find_pair(&[1010, 5, 1010 - 5], 2020));
+    let pair = find_pair(&[3, 1010, 7, 1010], 2020).unwrap();
+    assert_eq!((vec![1, 3], vec![1010, 1010]), (pair.indices, pair.values));
+    let pair = find_pair(&[1721, 979, 366, 299, 675, 1456], 2020).unwrap();
+    assert_eq!((vec![0, 3], vec![1721, 299]), (pair.indices, pair.values));
+
+    assert_eq!(0, count_pairs(&[1010], 2020));
+    assert_eq!(3, count_pairs(&[1010, 1010, 1010], 2020));
+    assert_eq!(4, count_pairs(&[1, 4, 1, 4], 5));
+    for (list, target) in [(vec![1, 4, 1, 4, 2, 3, 5, 0], 5), (vec![2; 5], 4)].iter() {
+      assert_eq!(all_sums(list, 2, *target).len(), count_pairs(list, *target));
+    }
+  }
+
+  #[test]
+  fn k_sums() {
+    let list = [1721, 979, 366, 299, 675, 1456];
+    let triple = find_sum(&list, 3, 2020).unwrap();
+    assert_eq!(vec![1, 2, 4], triple.indices);
+    assert_eq!(vec![979, 366, 675], triple.values);
+    assert_eq!(None, find_sum(&list, 4, 2020));
+    assert_eq!(
+      Some(vec![0, 1, 3, 4]),
+      find_sum(&list, 4, 3674).map(|m| m.indices)
+    );
+    assert_eq!(Some(vec![]), find_sum(&list, 0, 0).map(|m| m.values));
+    assert_eq!(None, find_sum(&list, 7, 5496));
+  }
+
+  #[test]
+  fn all_k_sums() {
+    let indices = |list: &[i64], k, target| -> Vec<Vec<usize>> {
+      all_sums(list, k, target)
+        .into_iter()
+        .map(|m| m.indices)
+        .collect()
+    };
+    assert_eq!(
+      vec![vec![0, 1], vec![0, 2], vec![1, 2]],
+      indices(&[1010, 1010, 1010], 2, 2020)
+    );
+    assert_eq!(
+      vec![vec![0, 1], vec![0, 3], vec![1, 2], vec![2, 3]],
+      indices(&[1, 4, 1, 4], 2, 5)
+    );
+    assert_eq!(
+      vec![vec![0, 1, 2], vec![0, 3, 4]],
+      indices(&[1, 2, 3, 0, 5], 3, 6)
+    );
+    assert!(indices(&[1010], 2, 2020).is_empty());
+  }
+
+  #[test]
+  fn streaming() {
+    let found = stream_pair(EXAMPLE.as_bytes(), 2020).unwrap();
+    assert_eq!(Some(514579), found.found.map(|m| m.product()));
+    assert_eq!(4, found.lines_read);
+    let found = stream_triple(EXAMPLE.as_bytes(), 2020).unwrap();
+    assert_eq!(
+      Some(Match {
+        indices: vec![1, 2, 4],
+        values: vec![979, 366, 675]
+      }),
+      found.found
+    );
+    assert_eq!(5, found.lines_read);
+
+    // Reading stops at the match, so what comes after it isn't looked at.
+    let found = stream_pair("1010\n\n5000\n1010\nnot a number\n".as_bytes(), 2020).unwrap();
+    assert_eq!(Some(vec![0, 2]), found.found.map(|m| m.indices));
+    assert_eq!(4, found.lines_read);
+    let found = stream_triple("1010\n1010\n".as_bytes(), 2020).unwrap();
+    assert_eq!((None, 2), (found.found, found.lines_read));
+
+    // Any entry can be part of a sum, and any target can be asked for,
+    // without memory growing with either.
+    let list = [-5, 2025, 0, 10_000_000_000, -1, 3, 1];
+    let text: Vec<String> = list.iter().map(|v| v.to_string()).collect();
+    let text = text.join("\n");
+    for target in [-5, -6, 2020, 2024, 10_000_000_000, 10_000_000_003].iter() {
+      assert_eq!(
+        find_pair(&list, *target),
+        stream_pair(text.as_bytes(), *target).unwrap().found,
+        "pair summing to {}",
+        target
+      );
+      let triple = stream_triple(text.as_bytes(), *target).unwrap().found;
+      assert_eq!(
+        find_sum(&list, 3, *target).is_some(),
+        triple.is_some(),
+        "triple summing to {}",
+        target
+      );
+      assert!(triple.is_none_or(|m| m.values.iter().sum::<i64>() == *target));
+    }
+    let extremes = format!("{}\n{}\n-1\n", i64::MIN, i64::MAX);
+    let found = stream_pair(format!("{}\n1\n", i64::MIN).as_bytes(), i64::MIN + 1).unwrap();
+    assert_eq!(Some(vec![0, 1]), found.found.map(|m| m.indices));
+    let found = stream_pair(extremes.as_bytes(), -1).unwrap().found;
+    assert_eq!(Some(vec![0, 1]), found.map(|m| m.indices));
+    assert_eq!(
+      None,
+      stream_pair(extremes.as_bytes(), i64::MAX).unwrap().found
+    );
+    let found = stream_triple(extremes.as_bytes(), -2).unwrap().found;
+    assert_eq!(Some(vec![0, 1, 2]), found.map(|m| m.indices));
+    let found = stream_triple("1010\n7\n1010\n0\n".as_bytes(), 2020).unwrap();
+    assert_eq!(Some(vec![0, 2, 3]), found.found.map(|m| m.indices));
+
+    match stream_pair("1\n2\nx\n".as_bytes(), 2020) {
+      Err(ReadError::Parse(e)) => assert_eq!((3, 1), (e.line, e.column)),
+      other => panic!("expected a parse error, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn streaming_my_input() {
+    let input = my_input!(1);
+    let found = stream_pair(input.as_bytes(), 2020).unwrap().found;
+    assert_eq!(Some(211899), found.map(|m| m.product()));
+    let found = stream_triple(input.as_bytes(), 2020).unwrap().found;
+    assert_eq!(Some(275765682), found.map(|m| m.product()));
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = problem("1721\n979\n3x6\n").unwrap_err();
+    assert_eq!((3, 1), (err.line, err.column));
+  }
 }
@@ -1,9 +1,8 @@
-pub fn problem(input: &str) -> u64 {
-  let mut nums: Vec<u64> = input
-    .trim()
-    .lines()
-    .map(|line| line.parse().unwrap())
-    .collect();
+use crate::parse::{self, ParseError};
+use crate::{Example, Solution};
+
+pub fn problem(input: &str) -> Result<u64, ParseError> {
+  let mut nums: Vec<u64> = parse::lines(input, parse::number)?;
   nums.sort();
   let mut one_diffs: u64 = 0;
   let mut three_diffs: u64 = 1; // one to account for the final diff
@@ -22,44 +21,81 @@ pub fn problem(input: &str) -> u64 {
     }
     current_jolts = num;
   }
-  one_diffs * three_diffs
+  Ok(one_diffs * three_diffs)
 }
 
-pub fn problem_part_2(input: &str) -> u64 {
-  let mut nums: Vec<u64> = input
-    .trim()
-    .lines()
-    .map(|line| line.parse().unwrap())
-    .collect();
+pub fn problem_part_2(input: &str) -> Result<u64, ParseError> {
+  let mut nums: Vec<u64> = parse::lines(input, parse::number)?;
   nums.sort();
 
   let reversed_numbers = nums.into_iter().rev().chain([0].iter().copied());
-  reversed_numbers
-    .fold((None, None, None), |prevs, num| {
-      let mut solutions_from_here = 0;
-      if let Some((val, count)) = prevs.0 {
-        if val - num <= 3 {
-          solutions_from_here += count;
+  Ok(
+    reversed_numbers
+      .fold((None, None, None), |prevs, num| {
+        let mut solutions_from_here = 0;
+        if let Some((val, count)) = prevs.0 {
+          if val - num <= 3 {
+            solutions_from_here += count;
+          }
+        } else {
+          solutions_from_here += 1;
         }
-      } else {
-        solutions_from_here += 1;
-      }
-      if let Some((val, count)) = prevs.1 {
-        if val - num <= 3 {
-          solutions_from_here += count;
+        if let Some((val, count)) = prevs.1 {
+          if val - num <= 3 {
+            solutions_from_here += count;
+          }
         }
-      }
-      if let Some((val, count)) = prevs.2 {
-        if val - num <= 3 {
-          solutions_from_here += count;
+        if let Some((val, count)) = prevs.2 {
+          if val - num <= 3 {
+            solutions_from_here += count;
+          }
         }
-      }
-      let result = (Some((num, solutions_from_here)), prevs.0, prevs.1);
-      result
-    })
-    .0
-    .unwrap()
-    .1
+        (Some((num, solutions_from_here)), prevs.0, prevs.1)
+      })
+      .0
+      .unwrap()
+      .1,
+  )
+}
+
+pub const EXAMPLE: &str = "\
+16
+10
+15
+5
+1
+11
+7
+19
+6
+12
+4
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = u64;
+  const DAY: u8 = 10;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<u64, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = u64;
+  const DAY: u8 = 10;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<u64, ParseError> {
+    problem_part_2(text)
+  }
 }
 
 #[cfg(test)]
@@ -68,40 +104,35 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(7 * 5, problem(EXAMPLE));
-    assert_eq!(22 * 10, problem(LONGER_EXAMPLE));
+    assert_eq!(Ok(7 * 5), problem(EXAMPLE));
+    assert_eq!(Ok(22 * 10), problem(LONGER_EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(1690, problem(MY_INPUT));
+    let input = my_input!(10);
+    assert_eq!(Ok(1690), problem(&input));
   }
 
   #[test]
   fn examples_part_2() {
-    assert_eq!(8, problem_part_2(EXAMPLE));
-    assert_eq!(19208, problem_part_2(LONGER_EXAMPLE));
+    assert_eq!(Ok(8), problem_part_2(EXAMPLE));
+    assert_eq!(Ok(19208), problem_part_2(LONGER_EXAMPLE));
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(5289227976704, problem_part_2(MY_INPUT));
+    let input = my_input!(10);
+    assert_eq!(Ok(5289227976704), problem_part_2(&input));
   }
 
-  const EXAMPLE: &'static str = "\
-16
-10
-15
-5
-1
-11
-7
-19
-6
-12
-4
-  ";
-  const LONGER_EXAMPLE: &'static str = "\
+  #[test]
+  fn malformed_input() {
+    let err = problem("16\n10\n\n1.5\n").unwrap_err();
+    assert_eq!((4, 1), (err.line, err.column));
+  }
+
+  const LONGER_EXAMPLE: &str = "\
 28
 33
 18
@@ -134,5 +165,4 @@ mod test {
 10
 3
 ";
-  static MY_INPUT: &'static str = include_str!("day_10_input.txt");
 }
@@ -1,64 +1,130 @@
+use crate::parse::{self, ParseError};
+use crate::{Example, Solution};
+use policy::Policy;
 use regex::Regex;
 
-struct ConstrainedPassword {
-  lower: usize,
-  upper: usize,
-  char: char,
-  password: String,
+pub mod audit;
+pub mod generator;
+pub mod policy;
+
+/// The two ways the puzzle reads a password's policy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interpretation {
+  /// The char appears between `lower` and `upper` times.
+  SledRental,
+  /// Exactly one of positions `lower` and `upper` holds the char.
+  Toboggan,
+}
+
+/// A line of the puzzle input: a password, and the policy it was set under.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ConstrainedPassword {
+  pub lower: usize,
+  pub upper: usize,
+  pub char: char,
+  pub password: String,
 }
 impl ConstrainedPassword {
+  pub fn parse(line: &str) -> Result<Self, ParseError> {
+    lazy_static! {
+      static ref LINE_RE: Regex = Regex::new(r"^([0-9]+)-([0-9]+) (.): (.*)$").unwrap();
+    }
+    let captures = LINE_RE
+      .captures(line)
+      .ok_or_else(|| ParseError::at(1, "expected a policy and password, like `1-3 a: abcde`"))?;
+    let number = |idx| {
+      let m = captures.get(idx).unwrap();
+      parse::number(m.as_str()).map_err(|e| e.offset(0, parse::column(line, m.start()) - 1))
+    };
+    Ok(ConstrainedPassword {
+      lower: number(1)?,
+      upper: number(2)?,
+      char: captures[3].chars().next().unwrap(),
+      password: captures[4].to_string(),
+    })
+  }
+
+  pub fn follows(&self, interpretation: Interpretation) -> bool {
+    match interpretation {
+      Interpretation::SledRental => self.is_valid(),
+      Interpretation::Toboggan => self.is_valid_2(),
+    }
+  }
+
   fn is_valid(&self) -> bool {
     let times = self.password.chars().filter(|c| *c == self.char).count();
     times >= self.lower && times <= self.upper
   }
 
+  /// Positions count chars from 1. A position outside the password doesn't
+  /// contain the policy's char.
   fn is_valid_2(&self) -> bool {
-    let b = format!("{}", self.char).as_bytes()[0];
-    let bytes: Vec<_> = self.password.bytes().collect();
-    let left = bytes[self.lower - 1] == b;
-    let right = bytes[self.upper - 1] == b;
-    match (left, right) {
-      (true, false) | (false, true) => true,
-      _ => false,
-    }
+    let holds_char = |position: usize| {
+      position
+        .checked_sub(1)
+        .and_then(|idx| self.password.chars().nth(idx))
+        == Some(self.char)
+    };
+    holds_char(self.lower) != holds_char(self.upper)
   }
 }
 
-pub fn problem(input: &str) -> Option<usize> {
-  let regex = Regex::new(r"(\d+)-(\d+) (.): (.*)").unwrap();
-
-  let list: Vec<_> = input
-    .trim()
-    .lines()
-    .map(|s| {
-      let captures = regex.captures(s).unwrap();
-      ConstrainedPassword {
-        lower: captures[1].parse().unwrap(),
-        upper: captures[2].parse().unwrap(),
-        char: captures[3].chars().next().unwrap(),
-        password: captures[4].to_string(),
-      }
-    })
-    .collect();
-  Some(list.iter().filter(|p| p.is_valid()).count())
+impl std::fmt::Display for ConstrainedPassword {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "{}-{} {}: {}",
+      self.lower, self.upper, self.char, self.password
+    )
+  }
 }
 
-pub fn problem_part_2(input: &str) -> Option<usize> {
-  let regex = Regex::new(r"(\d+)-(\d+) (.): (.*)").unwrap();
-  let list: Vec<_> = input
-    .trim()
-    .lines()
-    .map(|s| {
-      let captures = regex.captures(s).unwrap();
-      ConstrainedPassword {
-        lower: captures[1].parse().unwrap(),
-        upper: captures[2].parse().unwrap(),
-        char: captures[3].chars().next().unwrap(),
-        password: captures[4].to_string(),
-      }
-    })
-    .collect();
-  Some(list.iter().filter(|p| p.is_valid_2()).count())
+pub fn problem(input: &str) -> Result<Option<usize>, ParseError> {
+  let list = parse::lines(input, ConstrainedPassword::parse)?;
+  Ok(Some(list.iter().filter(|p| p.is_valid()).count()))
+}
+
+pub fn problem_part_2(input: &str) -> Result<Option<usize>, ParseError> {
+  let list = parse::lines(input, ConstrainedPassword::parse)?;
+  Ok(Some(list.iter().filter(|p| p.is_valid_2()).count()))
+}
+
+/// Counts the passwords that `policy` allows.
+pub fn problem_with_policy(input: &str, policy: &Policy) -> Result<usize, ParseError> {
+  let list = parse::lines(input, ConstrainedPassword::parse)?;
+  Ok(list.iter().filter(|p| policy.allows(p)).count())
+}
+
+pub const EXAMPLE: &str = "\
+1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = Option<usize>;
+  const DAY: u8 = 2;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<usize>, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = Option<usize>;
+  const DAY: u8 = 2;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<usize>, ParseError> {
+    problem_part_2(text)
+  }
 }
 
 #[cfg(test)]
@@ -67,28 +133,58 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(Some(2), problem(EXAMPLE));
+    assert_eq!(Ok(Some(2)), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(Some(422), problem(MY_INPUT));
+    let input = my_input!(2);
+    assert_eq!(Ok(Some(422)), problem(&input));
   }
 
   #[test]
   fn part_2_examples() {
-    assert_eq!(Some(1), problem_part_2(EXAMPLE));
+    assert_eq!(Ok(Some(1)), problem_part_2(EXAMPLE));
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(Some(451), problem_part_2(MY_INPUT));
+    let input = my_input!(2);
+    assert_eq!(Ok(Some(451)), problem_part_2(&input));
   }
 
-  static EXAMPLE: &'static str = "\
-1-3 a: abcde
-1-3 b: cdefg
-2-9 c: ccccccccc
-  ";
-  static MY_INPUT: &'static str = include_str!("day_2_input.txt");
+  #[test]
+  fn unicode_positions() {
+    let valid = |line| ConstrainedPassword::parse(line).unwrap().is_valid_2();
+    assert!(valid("1-3 é: éab"));
+    assert!(!valid("1-3 é: éaé"));
+    assert!(valid("2-4 b: ébcd"));
+    assert!(valid("2-3 🔑: a🔑b"));
+    assert!(valid("1-9 a: abc"));
+    assert!(!valid("4-9 a: abc"));
+    assert!(valid("0-1 a: abc"));
+    assert_eq!(
+      Ok(Some(3)),
+      problem_part_2("1-3 é: éab\n1-2 a: ab\n3-5 ß: aßß\n")
+    );
+  }
+
+  #[test]
+  fn policies_my_input() {
+    let input = my_input!(2);
+    let count = |policy| problem_with_policy(&input, &Policy::parse(policy).unwrap());
+    assert_eq!(Ok(422), count(policy::SLED_RENTAL));
+    assert_eq!(Ok(451), count(policy::TOBOGGAN));
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = problem("1-3 a: abcde\n1-3 b cdefg\n").unwrap_err();
+    assert_eq!((2, 1), (err.line, err.column));
+    let err = problem("1-3 a: abcde\n1-99999999999999999999 b: cdefg\n").unwrap_err();
+    assert_eq!((2, 3), (err.line, err.column));
+    // Only ASCII digits are numbers.
+    let err = problem("١-3 a: abcde\n").unwrap_err();
+    assert_eq!((1, 1), (err.line, err.column));
+  }
 }
@@ -0,0 +1,112 @@
+//! Checks every password in a list against a policy, and says why each one
+//! passed or failed, in a form that can be exported as CSV or JSON.
+
+use super::policy::Policy;
+use super::ConstrainedPassword;
+use crate::parse::ParseError;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct AuditEntry {
+  /// The 1-based line of the input the password was on.
+  pub line: usize,
+  pub lower: usize,
+  pub upper: usize,
+  pub char: char,
+  pub password: String,
+  pub allowed: bool,
+  pub reason: String,
+}
+
+pub fn audit(input: &str, policy: &Policy) -> Result<Vec<AuditEntry>, ParseError> {
+  input
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty())
+    .map(|(idx, line)| {
+      let entry = ConstrainedPassword::parse(line).map_err(|e| e.offset(idx, 0))?;
+      let verdict = policy.explain(&entry);
+      Ok(AuditEntry {
+        line: idx + 1,
+        lower: entry.lower,
+        upper: entry.upper,
+        char: entry.char,
+        password: entry.password,
+        allowed: verdict.allowed,
+        reason: verdict.reason,
+      })
+    })
+    .collect()
+}
+
+/// Writes one row per entry, after a header row naming the columns.
+pub fn write_csv(entries: &[AuditEntry], writer: impl Write) -> csv::Result<()> {
+  let mut writer = csv::Writer::from_writer(writer);
+  for entry in entries {
+    writer.serialize(entry)?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+/// Writes the entries as a JSON array of objects.
+pub fn write_json(entries: &[AuditEntry], writer: impl Write) -> serde_json::Result<()> {
+  serde_json::to_writer_pretty(writer, entries)
+}
+
+#[cfg(test)]
+mod test {
+  use super::super::{policy, EXAMPLE};
+  use super::*;
+
+  #[test]
+  fn audits_every_entry() {
+    let policy = Policy::parse(policy::SLED_RENTAL).unwrap();
+    let entries = audit(&format!("\n{}", EXAMPLE), &policy).unwrap();
+    let summary: Vec<_> = entries
+      .iter()
+      .map(|e| (e.line, e.allowed, e.reason.as_str()))
+      .collect();
+    assert_eq!(
+      vec![
+        (2, true, "character 'a' appears 1 time, expected 1-3"),
+        (3, false, "character 'b' appears 0 times, expected 1-3"),
+        (4, true, "character 'c' appears 9 times, expected 2-9"),
+      ],
+      summary
+    );
+    let err = audit("1-3 a: abcde\nnonsense\n", &policy).unwrap_err();
+    assert_eq!(2, err.line);
+  }
+
+  #[test]
+  fn exports() {
+    let policy = Policy::parse(policy::TOBOGGAN).unwrap();
+    let entries = audit("1-3 a: ab,cde\n", &policy).unwrap();
+
+    let mut csv = Vec::new();
+    write_csv(&entries, &mut csv).unwrap();
+    assert_eq!(
+      "line,lower,upper,char,password,allowed,reason\n\
+       1,1,3,a,\"ab,cde\",true,position 1 contains 'a'\n",
+      String::from_utf8(csv).unwrap()
+    );
+
+    let mut json = Vec::new();
+    write_json(&entries, &mut json).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(
+      serde_json::json!([{
+        "line": 1,
+        "lower": 1,
+        "upper": 3,
+        "char": "a",
+        "password": "ab,cde",
+        "allowed": true,
+        "reason": "position 1 contains 'a'",
+      }]),
+      json
+    );
+  }
+}
@@ -0,0 +1,279 @@
+//! Makes passwords that follow, or deliberately break, a given policy, for
+//! testing code that checks them.
+
+use super::{ConstrainedPassword, Interpretation};
+use crate::generate::Rng;
+
+pub struct PasswordGenerator {
+  rng: Rng,
+  alphabet: Vec<char>,
+}
+impl PasswordGenerator {
+  /// A generator of passwords made from a to z, and the policy's char.
+  pub fn new(seed: u64) -> Self {
+    Self::with_alphabet(seed, ('a'..='z').collect())
+  }
+
+  pub fn with_alphabet(seed: u64, alphabet: Vec<char>) -> Self {
+    Self {
+      rng: Rng::new(seed),
+      alphabet,
+    }
+  }
+
+  /// A password that follows `policy` under `interpretation`, or None if
+  /// none can, like when a policy's positions are the same under the
+  /// toboggan interpretation. The policy's own password is ignored.
+  pub fn passing(
+    &mut self,
+    policy: &ConstrainedPassword,
+    interpretation: Interpretation,
+  ) -> Option<ConstrainedPassword> {
+    self.generate(policy, interpretation, true)
+  }
+
+  /// A password that breaks `policy` under `interpretation`, or None if
+  /// every password follows it.
+  pub fn failing(
+    &mut self,
+    policy: &ConstrainedPassword,
+    interpretation: Interpretation,
+  ) -> Option<ConstrainedPassword> {
+    self.generate(policy, interpretation, false)
+  }
+
+  fn generate(
+    &mut self,
+    policy: &ConstrainedPassword,
+    interpretation: Interpretation,
+    passing: bool,
+  ) -> Option<ConstrainedPassword> {
+    let others: Vec<char> = self
+      .alphabet
+      .iter()
+      .copied()
+      .filter(|c| *c != policy.char)
+      .collect();
+    let password = match interpretation {
+      Interpretation::SledRental => self.counted(policy, &others, passing)?,
+      Interpretation::Toboggan => self.positioned(policy, &others, passing)?,
+    };
+    let generated = ConstrainedPassword {
+      password: password.into_iter().collect(),
+      ..policy.clone()
+    };
+    debug_assert_eq!(passing, generated.follows(interpretation));
+    Some(generated)
+  }
+
+  fn counted(
+    &mut self,
+    policy: &ConstrainedPassword,
+    others: &[char],
+    passing: bool,
+  ) -> Option<Vec<char>> {
+    let (lower, upper) = (policy.lower as i64, policy.upper as i64);
+    let count = if passing {
+      if lower > upper {
+        return None;
+      }
+      self.rng.range(lower, upper)
+    } else if lower > 0 && (lower > upper || self.rng.one_in(2)) {
+      self.rng.range(0, lower - 1)
+    } else {
+      self.rng.range(upper + 1, upper + 5)
+    };
+    let extra = if others.is_empty() {
+      0
+    } else {
+      self.rng.range(0, 8) as usize
+    };
+    let mut password = vec![policy.char; count as usize];
+    password.extend((0..extra).map(|_| *self.rng.choose(others)));
+    self.rng.shuffle(&mut password);
+    Some(password)
+  }
+
+  fn positioned(
+    &mut self,
+    policy: &ConstrainedPassword,
+    others: &[char],
+    passing: bool,
+  ) -> Option<Vec<char>> {
+    // Position 0 never holds the char, so it's fixed, and the other
+    // position can be chosen to make the password pass or fail.
+    let positions = [policy.lower, policy.upper];
+    let choosable: Vec<usize> = positions.iter().copied().filter(|p| *p > 0).collect();
+    let holding: Vec<usize> = match (passing, choosable.as_slice()) {
+      (_, [a, b]) if a == b => {
+        if passing {
+          return None;
+        }
+        if self.rng.one_in(2) {
+          vec![*a]
+        } else {
+          vec![]
+        }
+      }
+      (true, []) => return None,
+      (true, [only]) => vec![*only],
+      (true, _) => vec![*self.rng.choose(&choosable)],
+      (false, [_, _]) if self.rng.one_in(2) => choosable.clone(),
+      (false, _) => vec![],
+    };
+    if others.is_empty() && holding.len() < choosable.len() {
+      // Without other chars, a position can only be kept from holding the
+      // char by cutting the password short before it.
+      let missing = choosable.iter().filter(|p| !holding.contains(p)).min()?;
+      if holding.iter().any(|p| p > missing) {
+        return None;
+      }
+      return Some(vec![policy.char; missing - 1]);
+    }
+
+    let length = *positions.iter().max().unwrap() + self.rng.below(5);
+    let mut password: Vec<char> = (0..length)
+      .map(|_| *self.rng.choose(&self.alphabet))
+      .collect();
+    for position in choosable {
+      password[position - 1] = if holding.contains(&position) {
+        policy.char
+      } else {
+        *self.rng.choose(others)
+      };
+    }
+    Some(password)
+  }
+}
+
+/// Finds a smaller password that's still `interesting`, like a simpler
+/// example of a bug, by repeatedly dropping chars and replacing them with
+/// `a`, or `b` when the policy is about `a`, until no single change keeps it
+/// interesting. The policy is left as it is.
+pub fn shrink(
+  entry: &ConstrainedPassword,
+  mut interesting: impl FnMut(&ConstrainedPassword) -> bool,
+) -> ConstrainedPassword {
+  let mut smallest = entry.clone();
+  let mut chars: Vec<char> = entry.password.chars().collect();
+  // Replacing a char with the policy's own would change what's counted.
+  let simplest = if entry.char == 'a' { 'b' } else { 'a' };
+  'shrinking: loop {
+    let removals = (0..chars.len()).map(|idx| {
+      let mut candidate = chars.clone();
+      candidate.remove(idx);
+      candidate
+    });
+    let simplifications = (0..chars.len())
+      .filter(|idx| chars[*idx] != simplest)
+      .map(|idx| {
+        let mut candidate = chars.clone();
+        candidate[idx] = simplest;
+        candidate
+      });
+    for candidate in removals.chain(simplifications) {
+      let attempt = ConstrainedPassword {
+        password: candidate.iter().collect(),
+        ..smallest.clone()
+      };
+      if interesting(&attempt) {
+        smallest = attempt;
+        chars = candidate;
+        continue 'shrinking;
+      }
+    }
+    return smallest;
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use Interpretation::*;
+
+  fn policy(line: &str) -> ConstrainedPassword {
+    ConstrainedPassword::parse(line).unwrap()
+  }
+
+  #[test]
+  fn generates_passing_and_failing_passwords() {
+    let policies = [
+      "1-3 a: ", "2-9 c: ", "0-2 é: ", "3-3 z: ", "0-0 a: ", "4-1 b: ", "5-2 b: ",
+    ];
+    // Every password breaks these, and every other policy allows some.
+    let unpassable = [
+      ("3-3 z: ", Toboggan),
+      ("0-0 a: ", Toboggan),
+      ("4-1 b: ", SledRental),
+      ("5-2 b: ", SledRental),
+    ];
+    for line in policies.iter() {
+      let policy = policy(line);
+      for interpretation in [SledRental, Toboggan].iter().copied() {
+        let passable = !unpassable.contains(&(*line, interpretation));
+        for seed in 0..20 {
+          let mut generator = PasswordGenerator::new(seed);
+          let passing = generator.passing(&policy, interpretation);
+          assert_eq!(
+            passable,
+            passing.is_some(),
+            "{} under {:?}",
+            line,
+            interpretation
+          );
+          let failing = generator
+            .failing(&policy, interpretation)
+            .unwrap_or_else(|| panic!("{} under {:?}", line, interpretation));
+          if let Some(passing) = passing {
+            assert!(passing.follows(interpretation), "{}", passing);
+          }
+          assert!(!failing.follows(interpretation), "{}", failing);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn impossible_policies() {
+    let mut generator = PasswordGenerator::new(1);
+    assert_eq!(None, generator.passing(&policy("3-3 a: "), Toboggan));
+    assert_eq!(None, generator.passing(&policy("0-0 a: "), Toboggan));
+    assert_eq!(None, generator.passing(&policy("4-1 a: "), SledRental));
+    assert!(generator.failing(&policy("3-3 a: "), Toboggan).is_some());
+    // With nothing but the policy's char to use, position 1 can't be kept
+    // from holding it, and position 3 only by ending the password early.
+    let mut generator = PasswordGenerator::with_alphabet(1, vec!['a']);
+    let generated = (0..10)
+      .filter_map(|_| generator.passing(&policy("1-3 a: "), Toboggan))
+      .collect::<Vec<_>>();
+    assert!(!generated.is_empty());
+    assert!(generated.iter().all(|g| g.password == "aa"));
+  }
+
+  #[test]
+  fn seeds_are_reproducible() {
+    let generate = |seed| {
+      PasswordGenerator::new(seed)
+        .passing(&policy("1-3 a: "), SledRental)
+        .unwrap()
+    };
+    assert_eq!(generate(4), generate(4));
+  }
+
+  #[test]
+  fn shrinks_to_a_minimal_example() {
+    let failing = |interpretation| move |p: &ConstrainedPassword| !p.follows(interpretation);
+    let shrunk = shrink(&policy("2-3 a: xaxyaaaab"), failing(SledRental));
+    assert_eq!("aaaa", shrunk.password);
+    let shrunk = shrink(&policy("1-3 a: abab"), failing(Toboggan));
+    assert_eq!("", shrunk.password);
+    let shrunk = shrink(&policy("1-3 a: xyz"), |p| p.password.contains('z'));
+    assert_eq!("z", shrunk.password);
+    // Turning `x` into the policy's `a` would break it, so `x` becomes `b`.
+    let still_passing = |p: &ConstrainedPassword| p.password.len() == 2 && p.follows(SledRental);
+    let shrunk = shrink(&policy("1-1 a: xa"), still_passing);
+    assert_eq!("ba", shrunk.password);
+    let shrunk = shrink(&policy("1-1 c: xc"), still_passing);
+    assert_eq!("ac", shrunk.password);
+  }
+}
@@ -0,0 +1,702 @@
+//! A small language for password policies, so that a list of passwords can
+//! be checked against rules other than the two the puzzle describes.
+//!
+//! A policy is made of rules, combined with `not`, `and`, `xor` and `or`
+//! (binding in that order, tightest first) and grouped with parentheses:
+//!
+//! - `count T RANGE`: the password has RANGE of T, where T is a quoted char
+//!   like `'a'` or a class: `digit` (0 to 9), `upper`, `lower`, `letter`,
+//!   `symbol` (anything else but a space) or `space`.
+//! - `has T` and `forbid T`: short for `count T 1+` and `count T 0`.
+//! - `at N T`: the char at position N, counting from 1, is T.
+//! - `length RANGE`: the password is RANGE chars long.
+//!
+//! A RANGE is `N`, `N-M` or `N+`. In place of a char or number, `char`,
+//! `lower` and `upper` stand for the parts of the policy on the password's
+//! own line, so the puzzle's two policies are `SLED_RENTAL` and `TOBOGGAN`.
+
+use super::ConstrainedPassword;
+use crate::parse::ParseError;
+use std::str::FromStr;
+
+/// Part 1's policy.
+pub const SLED_RENTAL: &str = "count char lower-upper";
+/// Part 2's policy.
+pub const TOBOGGAN: &str = "at lower char xor at upper char";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Policy {
+  Count(Target, Range),
+  At(Number, Target),
+  Length(Range),
+  Not(Box<Policy>),
+  And(Box<Policy>, Box<Policy>),
+  Xor(Box<Policy>, Box<Policy>),
+  Or(Box<Policy>, Box<Policy>),
+}
+impl Policy {
+  pub fn parse(text: &str) -> Result<Self, ParseError> {
+    let mut parser = Parser {
+      tokens: tokenize(text)?,
+      position: 0,
+      end: text.chars().count() + 1,
+    };
+    let policy = parser.or()?;
+    match parser.peek() {
+      None => Ok(policy),
+      Some(token) => Err(ParseError::at(
+        token.column,
+        format!("expected `and`, `xor` or `or`, got {}", token.kind),
+      )),
+    }
+  }
+
+  pub fn allows(&self, entry: &ConstrainedPassword) -> bool {
+    match self {
+      Policy::Count(target, range) => {
+        let count = entry
+          .password
+          .chars()
+          .filter(|c| target.matches(*c, entry))
+          .count();
+        range.contains(count, entry)
+      }
+      Policy::At(position, target) => match position.resolve(entry) {
+        0 => false,
+        position => entry
+          .password
+          .chars()
+          .nth(position - 1)
+          .is_some_and(|c| target.matches(c, entry)),
+      },
+      Policy::Length(range) => range.contains(entry.password.chars().count(), entry),
+      Policy::Not(policy) => !policy.allows(entry),
+      Policy::And(left, right) => left.allows(entry) && right.allows(entry),
+      Policy::Xor(left, right) => left.allows(entry) != right.allows(entry),
+      Policy::Or(left, right) => left.allows(entry) || right.allows(entry),
+    }
+  }
+
+  /// Like `allows`, but also says what about the password decided it.
+  pub fn explain(&self, entry: &ConstrainedPassword) -> Verdict {
+    match self {
+      Policy::Count(target, range) => {
+        let count = entry
+          .password
+          .chars()
+          .filter(|c| target.matches(*c, entry))
+          .count();
+        let times = if count == 1 { "time" } else { "times" };
+        let found = match target {
+          Target::Class(class) => format!("{} characters appear", class.name()),
+          _ => format!("character {} appears", target.describe(entry)),
+        };
+        Verdict {
+          allowed: range.contains(count, entry),
+          reason: format!(
+            "{} {} {}, expected {}",
+            found,
+            count,
+            times,
+            range.describe(entry)
+          ),
+        }
+      }
+      Policy::At(position, target) => {
+        let position = position.resolve(entry);
+        let found = position
+          .checked_sub(1)
+          .and_then(|idx| entry.password.chars().nth(idx));
+        match found {
+          None => Verdict {
+            allowed: false,
+            reason: format!(
+              "position {} is outside the {} character password",
+              position,
+              entry.password.chars().count()
+            ),
+          },
+          Some(c) if target.matches(c, entry) => Verdict {
+            allowed: true,
+            reason: format!("position {} contains {:?}", position, c),
+          },
+          Some(c) => Verdict {
+            allowed: false,
+            reason: format!(
+              "position {} contains {:?}, not {}",
+              position,
+              c,
+              target.describe(entry)
+            ),
+          },
+        }
+      }
+      Policy::Length(range) => {
+        let length = entry.password.chars().count();
+        Verdict {
+          allowed: range.contains(length, entry),
+          reason: format!(
+            "password is {} characters long, expected {}",
+            length,
+            range.describe(entry)
+          ),
+        }
+      }
+      Policy::Not(policy) => {
+        let verdict = policy.explain(entry);
+        let reason = if verdict.allowed {
+          format!("matched a forbidden rule: {}", verdict.reason)
+        } else {
+          format!("avoided a forbidden rule: {}", verdict.reason)
+        };
+        Verdict {
+          allowed: !verdict.allowed,
+          reason,
+        }
+      }
+      Policy::And(left, right) => {
+        let (left, right) = (left.explain(entry), right.explain(entry));
+        match (left.allowed, right.allowed) {
+          (true, true) => Verdict::joined(true, &left, " and ", &right),
+          (false, false) => Verdict::joined(false, &left, "; ", &right),
+          (true, false) => right,
+          (false, true) => left,
+        }
+      }
+      Policy::Xor(left, right) => {
+        let (left, right) = (left.explain(entry), right.explain(entry));
+        match (left.allowed, right.allowed) {
+          (true, true) => Verdict {
+            allowed: false,
+            reason: format!("both {} and {}", left.reason, right.reason),
+          },
+          (false, false) => Verdict {
+            allowed: false,
+            reason: format!("neither {} nor {}", left.reason, right.reason),
+          },
+          (true, false) => left,
+          (false, true) => right,
+        }
+      }
+      Policy::Or(left, right) => {
+        let left = left.explain(entry);
+        if left.allowed {
+          return left;
+        }
+        let right = right.explain(entry);
+        if right.allowed {
+          return right;
+        }
+        Verdict::joined(false, &left, "; ", &right)
+      }
+    }
+  }
+}
+
+/// Whether a policy allows a password, and why.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Verdict {
+  pub allowed: bool,
+  pub reason: String,
+}
+impl Verdict {
+  fn joined(allowed: bool, left: &Verdict, separator: &str, right: &Verdict) -> Self {
+    Self {
+      allowed,
+      reason: format!("{}{}{}", left.reason, separator, right.reason),
+    }
+  }
+}
+impl FromStr for Policy {
+  type Err = ParseError;
+
+  fn from_str(text: &str) -> Result<Self, ParseError> {
+    Self::parse(text)
+  }
+}
+
+/// The chars a rule looks for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+  Char(char),
+  /// The char in the password's own policy.
+  EntryChar,
+  Class(Class),
+}
+impl Target {
+  fn matches(self, c: char, entry: &ConstrainedPassword) -> bool {
+    match self {
+      Target::Char(target) => c == target,
+      Target::EntryChar => c == entry.char,
+      Target::Class(class) => class.contains(c),
+    }
+  }
+
+  fn describe(self, entry: &ConstrainedPassword) -> String {
+    match self {
+      Target::Char(c) => format!("{:?}", c),
+      Target::EntryChar => format!("{:?}", entry.char),
+      Target::Class(class) => format!("{} character", class.name()),
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Class {
+  Digit,
+  Upper,
+  Lower,
+  Letter,
+  Symbol,
+  Space,
+}
+impl Class {
+  fn named(name: &str) -> Option<Self> {
+    Some(match name {
+      "digit" => Class::Digit,
+      "upper" => Class::Upper,
+      "lower" => Class::Lower,
+      "letter" => Class::Letter,
+      "symbol" => Class::Symbol,
+      "space" => Class::Space,
+      _ => return None,
+    })
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Class::Digit => "digit",
+      Class::Upper => "upper",
+      Class::Lower => "lower",
+      Class::Letter => "letter",
+      Class::Symbol => "symbol",
+      Class::Space => "space",
+    }
+  }
+
+  pub fn contains(self, c: char) -> bool {
+    match self {
+      // Only 0 to 9: `½` and `²` are numeric, but aren't digits.
+      Class::Digit => c.is_ascii_digit(),
+      Class::Upper => c.is_uppercase(),
+      Class::Lower => c.is_lowercase(),
+      Class::Letter => c.is_alphabetic(),
+      Class::Symbol => !c.is_ascii_digit() && !c.is_alphabetic() && !c.is_whitespace(),
+      Class::Space => c.is_whitespace(),
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Number {
+  Literal(usize),
+  /// The lower number in the password's own policy.
+  EntryLower,
+  /// The upper number in the password's own policy.
+  EntryUpper,
+}
+impl Number {
+  fn resolve(self, entry: &ConstrainedPassword) -> usize {
+    match self {
+      Number::Literal(n) => n,
+      Number::EntryLower => entry.lower,
+      Number::EntryUpper => entry.upper,
+    }
+  }
+}
+
+/// An inclusive range, with no maximum for ranges like `8+`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Range {
+  pub min: Number,
+  pub max: Option<Number>,
+}
+impl Range {
+  fn contains(self, n: usize, entry: &ConstrainedPassword) -> bool {
+    n >= self.min.resolve(entry) && self.max.is_none_or(|max| n <= max.resolve(entry))
+  }
+
+  fn describe(self, entry: &ConstrainedPassword) -> String {
+    let min = self.min.resolve(entry);
+    match self.max.map(|max| max.resolve(entry)) {
+      None => format!("{}+", min),
+      Some(max) if max == min => min.to_string(),
+      Some(max) => format!("{}-{}", min, max),
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum TokenKind {
+  Word(String),
+  Number(usize),
+  Char(char),
+  Symbol(char),
+}
+impl std::fmt::Display for TokenKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      TokenKind::Word(word) => write!(f, "`{}`", word),
+      TokenKind::Number(n) => write!(f, "`{}`", n),
+      TokenKind::Char(c) => write!(f, "`{:?}`", c),
+      TokenKind::Symbol(c) => write!(f, "`{}`", c),
+    }
+  }
+}
+
+struct Token {
+  kind: TokenKind,
+  /// 1-based, in chars.
+  column: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+  let mut tokens = Vec::new();
+  let mut chars = text.chars().enumerate().peekable();
+  while let Some((idx, c)) = chars.next() {
+    let column = idx + 1;
+    let kind = match c {
+      _ if c.is_whitespace() => continue,
+      '(' | ')' | '-' | '+' => TokenKind::Symbol(c),
+      '\'' => match (chars.next(), chars.next()) {
+        (Some((_, c)), Some((_, '\''))) => TokenKind::Char(c),
+        _ => {
+          return Err(ParseError::at(
+            column,
+            "expected a single char between quotes, like 'a'",
+          ))
+        }
+      },
+      _ if c.is_ascii_digit() => {
+        let mut digits = c.to_string();
+        while let Some((_, c)) = chars.peek().filter(|(_, c)| c.is_ascii_digit()) {
+          digits.push(*c);
+          chars.next();
+        }
+        let n = digits
+          .parse()
+          .map_err(|e| ParseError::at(column, format!("number {} is too big: {}", digits, e)))?;
+        TokenKind::Number(n)
+      }
+      _ if c.is_alphabetic() => {
+        let mut word = c.to_string();
+        while let Some((_, c)) = chars.peek().filter(|(_, c)| c.is_alphabetic()) {
+          word.push(*c);
+          chars.next();
+        }
+        TokenKind::Word(word)
+      }
+      _ => return Err(ParseError::at(column, format!("unexpected {:?}", c))),
+    };
+    tokens.push(Token { kind, column });
+  }
+  Ok(tokens)
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  position: usize,
+  /// The column just past the end of the text, for errors about what's
+  /// missing.
+  end: usize,
+}
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position)
+  }
+
+  fn next(&mut self, expected: &str) -> Result<&Token, ParseError> {
+    let end = self.end;
+    let token = self
+      .tokens
+      .get(self.position)
+      .ok_or_else(|| ParseError::at(end, format!("expected {}, got the end", expected)))?;
+    self.position += 1;
+    Ok(token)
+  }
+
+  fn error(&self, token: &Token, expected: &str) -> ParseError {
+    ParseError::at(
+      token.column,
+      format!("expected {}, got {}", expected, token.kind),
+    )
+  }
+
+  fn eat_word(&mut self, word: &str) -> bool {
+    match self.peek() {
+      Some(Token {
+        kind: TokenKind::Word(w),
+        ..
+      }) if w == word => {
+        self.position += 1;
+        true
+      }
+      _ => false,
+    }
+  }
+
+  fn eat_symbol(&mut self, symbol: char) -> bool {
+    match self.peek() {
+      Some(Token {
+        kind: TokenKind::Symbol(s),
+        ..
+      }) if *s == symbol => {
+        self.position += 1;
+        true
+      }
+      _ => false,
+    }
+  }
+
+  fn or(&mut self) -> Result<Policy, ParseError> {
+    let mut policy = self.xor()?;
+    while self.eat_word("or") {
+      policy = Policy::Or(Box::new(policy), Box::new(self.xor()?));
+    }
+    Ok(policy)
+  }
+
+  fn xor(&mut self) -> Result<Policy, ParseError> {
+    let mut policy = self.and()?;
+    while self.eat_word("xor") {
+      policy = Policy::Xor(Box::new(policy), Box::new(self.and()?));
+    }
+    Ok(policy)
+  }
+
+  fn and(&mut self) -> Result<Policy, ParseError> {
+    let mut policy = self.not()?;
+    while self.eat_word("and") {
+      policy = Policy::And(Box::new(policy), Box::new(self.not()?));
+    }
+    Ok(policy)
+  }
+
+  fn not(&mut self) -> Result<Policy, ParseError> {
+    if self.eat_word("not") {
+      return Ok(Policy::Not(Box::new(self.not()?)));
+    }
+    if self.eat_symbol('(') {
+      let policy = self.or()?;
+      let token = self.next("`)`")?;
+      if token.kind != TokenKind::Symbol(')') {
+        return Err(self.error(&self.tokens[self.position - 1], "`)`"));
+      }
+      return Ok(policy);
+    }
+    self.rule()
+  }
+
+  fn rule(&mut self) -> Result<Policy, ParseError> {
+    const RULES: &str = "a rule: `count`, `has`, `forbid`, `at` or `length`";
+    let token = self.next(RULES)?;
+    let rule = match &token.kind {
+      TokenKind::Word(word) => word.clone(),
+      _ => return Err(self.error(&self.tokens[self.position - 1], RULES)),
+    };
+    let unbounded = |min| Range {
+      min: Number::Literal(min),
+      max: None,
+    };
+    Ok(match rule.as_str() {
+      "count" => Policy::Count(self.target()?, self.range()?),
+      "has" => Policy::Count(self.target()?, unbounded(1)),
+      "forbid" => Policy::Count(
+        self.target()?,
+        Range {
+          min: Number::Literal(0),
+          max: Some(Number::Literal(0)),
+        },
+      ),
+      "at" => {
+        let position = self.number()?;
+        if position == Number::Literal(0) {
+          let column = self.tokens[self.position - 1].column;
+          return Err(ParseError::at(column, "positions start at 1"));
+        }
+        Policy::At(position, self.target()?)
+      }
+      "length" => Policy::Length(self.range()?),
+      _ => return Err(self.error(&self.tokens[self.position - 1], RULES)),
+    })
+  }
+
+  fn target(&mut self) -> Result<Target, ParseError> {
+    const TARGETS: &str = "a quoted char, `char` or a class of chars";
+    let token = self.next(TARGETS)?;
+    let target = match &token.kind {
+      TokenKind::Char(c) => Some(Target::Char(*c)),
+      TokenKind::Word(word) if word == "char" => Some(Target::EntryChar),
+      TokenKind::Word(word) => Class::named(word).map(Target::Class),
+      _ => None,
+    };
+    target.ok_or_else(|| self.error(&self.tokens[self.position - 1], TARGETS))
+  }
+
+  fn number(&mut self) -> Result<Number, ParseError> {
+    const NUMBERS: &str = "a number, `lower` or `upper`";
+    let token = self.next(NUMBERS)?;
+    let number = match &token.kind {
+      TokenKind::Number(n) => Some(Number::Literal(*n)),
+      TokenKind::Word(word) if word == "lower" => Some(Number::EntryLower),
+      TokenKind::Word(word) if word == "upper" => Some(Number::EntryUpper),
+      _ => None,
+    };
+    number.ok_or_else(|| self.error(&self.tokens[self.position - 1], NUMBERS))
+  }
+
+  fn range(&mut self) -> Result<Range, ParseError> {
+    let min = self.number()?;
+    if self.eat_symbol('+') {
+      return Ok(Range { min, max: None });
+    }
+    if self.eat_symbol('-') {
+      let max = self.number()?;
+      return Ok(Range {
+        min,
+        max: Some(max),
+      });
+    }
+    Ok(Range {
+      min,
+      max: Some(min),
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn allows(policy: &str, line: &str) -> bool {
+    let entry = ConstrainedPassword::parse(line).unwrap();
+    Policy::parse(policy).unwrap().allows(&entry)
+  }
+
+  #[test]
+  fn puzzle_policies() {
+    let sled_rental = Policy::parse(SLED_RENTAL).unwrap();
+    let toboggan = Policy::parse(TOBOGGAN).unwrap();
+    let allowed = |policy: &Policy| -> Vec<bool> {
+      super::super::EXAMPLE
+        .lines()
+        .map(|line| policy.allows(&ConstrainedPassword::parse(line).unwrap()))
+        .collect()
+    };
+    assert_eq!(vec![true, false, true], allowed(&sled_rental));
+    assert_eq!(vec![true, false, false], allowed(&toboggan));
+  }
+
+  #[test]
+  fn rules() {
+    assert!(allows(
+      "length 8+ and has digit and has upper",
+      "1-1 a: Passw0rdy"
+    ));
+    assert!(!allows(
+      "length 8+ and has digit and has upper",
+      "1-1 a: Passw0r"
+    ));
+    assert!(allows("forbid ' ' and count symbol 1-2", "1-1 a: a!b?c"));
+    assert!(!allows("forbid ' '", "1-1 a: a b"));
+    assert!(allows("at 1 upper and at 3 'ß'", "1-1 a: Paßword"));
+    assert!(!allows("at 9 letter", "1-1 a: short"));
+    assert!(allows("count lower 5", "1-1 a: short"));
+    assert!(allows("at upper char", "1-3 a: bba"));
+    assert!(!allows("has digit", "1-1 a: ½²Ⅷ"));
+    assert!(allows("count digit 2 and count symbol 2", "1-1 a: ½4²2"));
+  }
+
+  #[test]
+  fn precedence() {
+    // `not` binds tighter than `and`, which binds tighter than `xor`, which
+    // binds tighter than `or`.
+    assert!(allows("not has 'a' and has 'b'", "1-1 a: b"));
+    assert!(!allows("not (has 'a' and has 'b')", "1-1 a: ab"));
+    assert!(allows("has 'a' or has 'x' xor has 'b'", "1-1 a: ab"));
+    assert!(!allows("(has 'a' or has 'x') xor has 'b'", "1-1 a: ab"));
+    assert!(allows("has 'x' and has 'y' or has 'a'", "1-1 a: a"));
+  }
+
+  #[test]
+  fn explanations() {
+    let explain = |policy: &str, line: &str| {
+      let entry = ConstrainedPassword::parse(line).unwrap();
+      let verdict = Policy::parse(policy).unwrap().explain(&entry);
+      (verdict.allowed, verdict.reason)
+    };
+    assert_eq!(
+      (
+        false,
+        "character 'b' appears 0 times, expected 1-3".to_string()
+      ),
+      explain(SLED_RENTAL, "1-3 b: cdefg")
+    );
+    assert_eq!(
+      (
+        false,
+        "both position 2 contains 'c' and position 9 contains 'c'".to_string()
+      ),
+      explain(TOBOGGAN, "2-9 c: ccccccccc")
+    );
+    assert_eq!(
+      (true, "position 1 contains 'a'".to_string()),
+      explain(TOBOGGAN, "1-3 a: abcde")
+    );
+    assert_eq!(
+      (
+        false,
+        "password is 5 characters long, expected 8+; digit characters appear 0 times, expected 1+"
+          .to_string()
+      ),
+      explain("length 8+ and has digit", "1-3 a: abcde")
+    );
+    assert_eq!(
+      (
+        false,
+        "position 9 is outside the 5 character password".to_string()
+      ),
+      explain("at 9 upper", "1-3 a: abcde")
+    );
+    assert_eq!(
+      (
+        false,
+        "matched a forbidden rule: position 1 contains 'a'".to_string()
+      ),
+      explain("not at 1 'a'", "1-3 a: abcde")
+    );
+    assert_eq!(
+      (
+        true,
+        "avoided a forbidden rule: character 'z' appears 0 times, expected 1+".to_string()
+      ),
+      explain("not has 'z'", "1-3 a: abcde")
+    );
+  }
+
+  #[test]
+  fn malformed_policies() {
+    let error = |policy: &str| {
+      let err = Policy::parse(policy).unwrap_err();
+      (err.column, err.message)
+    };
+    assert_eq!(
+      (
+        7,
+        "expected a quoted char, `char` or a class of chars, got `vowel`".to_string()
+      ),
+      error("count vowel 1-3")
+    );
+    assert_eq!(
+      (9, "expected `)`, got the end".to_string()),
+      error("(has 'a'")
+    );
+    assert_eq!((4, "positions start at 1".to_string()), error("at 0 'a'"));
+    assert_eq!(
+      (9, "expected `and`, `xor` or `or`, got `has`".to_string()),
+      error("has 'a' has 'b'")
+    );
+    assert_eq!(1, error("'ab'").0);
+    assert_eq!(5, error("has *").0);
+  }
+}
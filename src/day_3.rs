@@ -1,35 +1,92 @@
-type Point = (usize, usize);
+use crate::grid::{Edges, Grid, Point};
+use crate::parse::ParseError;
+use crate::{Example, Solution};
 
-struct Map {
-  patterns: Vec<Vec<bool>>,
+pub mod bits;
+pub mod render;
+
+pub use bits::BitRows;
+
+/// How a map stores where its trees are.
+pub trait Trees: Sized {
+  /// Reads a map of `.` and `#` squares, as `Grid::parse` would.
+  fn parse(input: &str) -> Result<Self, ParseError>;
+  fn width(&self) -> usize;
+  fn height(&self) -> usize;
+  /// Whether there's a tree at `x` in row `y`, both within the map.
+  fn is_tree(&self, x: usize, y: usize) -> bool;
+
+  fn count(&self) -> usize {
+    (0..self.height())
+      .map(|y| (0..self.width()).filter(|x| self.is_tree(*x, y)).count())
+      .sum()
+  }
+}
+impl Trees for Grid<bool> {
+  fn parse(input: &str) -> Result<Self, ParseError> {
+    Grid::parse(input, Edges::WrapHorizontal, square)
+  }
+
+  fn width(&self) -> usize {
+    self.width()
+  }
+
+  fn height(&self) -> usize {
+    self.height()
+  }
+
+  fn is_tree(&self, x: usize, y: usize) -> bool {
+    self.row(y)[x]
+  }
 }
-impl Map {
-  fn parse(str: &str) -> Self {
-    let patterns = str
-      .lines()
-      .map(|line| {
-        line
-          .bytes()
-          .map(|b| match b {
-            b'.' => false,
-            b'#' => true,
-            _ => panic!("Unknown byte: {}", b),
-          })
-          .collect()
-      })
-      .collect();
-    Map { patterns }
-  }
-
-  fn is_tree(&self, (x, y): Point) -> bool {
-    let pattern = &self.patterns[y];
-    pattern[x % pattern.len()]
-  }
-
-  fn count_trees_along_slope(&self, slope: Point) -> usize {
+
+/// Whether a square of the map is a tree.
+fn square(c: char) -> Option<bool> {
+  match c {
+    '.' => Some(false),
+    '#' => Some(true),
+    _ => None,
+  }
+}
+
+/// A forest, where each row of trees repeats forever to the right.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Map<T = Grid<bool>> {
+  trees: T,
+}
+impl<T: Trees> Map<T> {
+  pub fn parse(str: &str) -> Result<Self, ParseError> {
+    Ok(Map {
+      trees: T::parse(str)?,
+    })
+  }
+
+  pub fn width(&self) -> usize {
+    self.trees.width()
+  }
+
+  pub fn height(&self) -> usize {
+    self.trees.height()
+  }
+
+  pub fn is_tree(&self, (x, y): Point) -> bool {
+    self.width() > 0 && y < self.height() && self.trees.is_tree(x % self.width(), y)
+  }
+
+  /// How many trees there are in one repeat of the map.
+  pub fn count_trees(&self) -> usize {
+    self.trees.count()
+  }
+
+  /// A slope that doesn't go down never reaches the bottom, so it hits no
+  /// trees.
+  pub fn count_trees_along_slope(&self, slope: Point) -> usize {
+    if slope.1 == 0 {
+      return 0;
+    }
     let mut count = 0;
     let mut current = (0, 0);
-    while current.1 < self.patterns.len() {
+    while current.1 < self.height() {
       if self.is_tree(current) {
         count += 1;
       }
@@ -37,19 +94,146 @@ impl Map {
     }
     count
   }
+
+  /// Like `count_trees_along_slope` for each of `slopes`, but in a single pass
+  /// down the map, so each row is only visited once.
+  pub fn count_trees_along_slopes(&self, slopes: &[Point]) -> Vec<usize> {
+    let mut counts = vec![0; slopes.len()];
+    let width = self.width().max(1);
+    for y in 0..self.height() {
+      for ((dx, dy), count) in slopes.iter().zip(counts.iter_mut()) {
+        if *dy != 0 && y % dy == 0 && self.trees.is_tree(y / dy * dx % width, y) {
+          *count += 1;
+        }
+      }
+    }
+    counts
+  }
+
+  /// How many trees each slope with a `dx` of up to `max_dx` and a `dy` from
+  /// 1 to `max_dy` hits, fewest first. Slopes that go down by the same `dy`
+  /// visit the same rows, so they're all counted in one pass over those rows,
+  /// and slopes whose `dx` differ by the map's width hit the same trees, so
+  /// they're only counted once.
+  pub fn rank_slopes(&self, max_dx: usize, max_dy: usize) -> Vec<(Point, usize)> {
+    let width = self.width().max(1);
+    let mut ranked = Vec::new();
+    for dy in 1..=max_dy {
+      let mut counts = vec![0; width.min(max_dx + 1)];
+      for (step, y) in (0..self.height()).step_by(dy).enumerate() {
+        for (dx, count) in counts.iter_mut().enumerate() {
+          if self.trees.is_tree(step * dx % width, y) {
+            *count += 1;
+          }
+        }
+      }
+      ranked.extend((0..=max_dx).map(|dx| ((dx, dy), counts[dx % width])));
+    }
+    ranked.sort_by_key(|(slope, trees)| (*trees, *slope));
+    ranked
+  }
+
+  /// The slope within the same bounds as `rank_slopes` that hits the fewest
+  /// trees, and how many it hits.
+  pub fn best_slope(&self, max_dx: usize, max_dy: usize) -> Option<(Point, usize)> {
+    self.rank_slopes(max_dx, max_dy).into_iter().next()
+  }
+}
+
+const SLOPES: [Point; 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+pub fn problem(input: &str) -> Result<usize, ParseError> {
+  problem_with::<Grid<bool>>(input)
+}
+
+/// Like `problem`, storing the map's trees in a `T`.
+pub fn problem_with<T: Trees>(input: &str) -> Result<usize, ParseError> {
+  Ok(Map::<T>::parse(input)?.count_trees_along_slope((3, 1)))
+}
+
+/// The product of the trees hit along each of the puzzle's slopes, or None
+/// if it's too big for a `u128`, which takes a map tens of millions of rows
+/// tall.
+pub fn problem_part_2(input: &str) -> Result<Option<u128>, ParseError> {
+  problem_part_2_with::<Grid<bool>>(input)
+}
+
+pub fn problem_part_2_with<T: Trees>(input: &str) -> Result<Option<u128>, ParseError> {
+  let map = Map::<T>::parse(input)?;
+  Ok(
+    map
+      .count_trees_along_slopes(&SLOPES)
+      .into_iter()
+      .try_fold(1u128, |product, count| product.checked_mul(count as u128)),
+  )
+}
+
+pub const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = usize;
+  const DAY: u8 = 3;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<usize, ParseError> {
+    problem(text)
+  }
 }
 
-pub fn problem(input: &str) -> usize {
-  Map::parse(input).count_trees_along_slope((3, 1))
+pub struct Part1BitPacked;
+impl Solution for Part1BitPacked {
+  type Input = ();
+  type Answer = usize;
+  const DAY: u8 = 3;
+  const PART: u8 = 1;
+  const NAME: &'static str = "bit-packed";
+  const EXAMPLE: Option<Example> = Part1::EXAMPLE;
+
+  fn solve(text: &str, _input: ()) -> Result<usize, ParseError> {
+    problem_with::<BitRows>(text)
+  }
 }
 
-pub fn problem_part_2(input: &str) -> usize {
-  let map = Map::parse(input);
-  let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
-  slopes
-    .iter()
-    .map(|slope| map.count_trees_along_slope(*slope))
-    .product()
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = Option<u128>;
+  const DAY: u8 = 3;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<u128>, ParseError> {
+    problem_part_2(text)
+  }
+}
+
+pub struct Part2BitPacked;
+impl Solution for Part2BitPacked {
+  type Input = ();
+  type Answer = Option<u128>;
+  const DAY: u8 = 3;
+  const PART: u8 = 2;
+  const NAME: &'static str = "bit-packed";
+  const EXAMPLE: Option<Example> = Part2::EXAMPLE;
+
+  fn solve(text: &str, _input: ()) -> Result<Option<u128>, ParseError> {
+    problem_part_2_with::<BitRows>(text)
+  }
 }
 
 #[cfg(test)]
@@ -58,36 +242,56 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(7, problem(EXAMPLE));
+    assert_eq!(Ok(7), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(209, problem(MY_INPUT));
+    let input = my_input!(3);
+    assert_eq!(Ok(209), problem(&input));
   }
 
   #[test]
   fn part_2_examples() {
-    assert_eq!(336, problem_part_2(EXAMPLE));
+    assert_eq!(Ok(Some(336)), problem_part_2(EXAMPLE));
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(1574890240, problem_part_2(MY_INPUT));
+    let input = my_input!(3);
+    assert_eq!(Ok(Some(1574890240)), problem_part_2(&input));
   }
 
-  static EXAMPLE: &'static str = "\
-..##.......
-#...#...#..
-.#....#..#.
-..#.#...#.#
-.#...##..#.
-..#.##.....
-.#.#.#....#
-.#........#
-#.##...#...
-#...##....#
-.#..#...#.#\
-  ";
-  static MY_INPUT: &'static str = include_str!("day_3_input.txt");
+  #[test]
+  fn ranks_slopes() {
+    let map: Map = Map::parse(EXAMPLE).unwrap();
+    let ranked = map.rank_slopes(25, 4);
+    assert_eq!(26 * 4, ranked.len());
+    for (slope, trees) in ranked.iter() {
+      assert_eq!(map.count_trees_along_slope(*slope), *trees, "{:?}", slope);
+    }
+    assert!(ranked.windows(2).all(|w| w[0].1 <= w[1].1));
+    assert_eq!(Some(((1, 3), 0)), map.best_slope(25, 4));
+    assert_eq!(Some(((2, 1), 1)), map.best_slope(3, 1));
+  }
+
+  #[test]
+  fn bit_packed_my_input() {
+    let input = my_input!(3);
+    assert_eq!(Ok(209), problem_with::<BitRows>(&input));
+    assert_eq!(Ok(Some(1574890240)), problem_part_2_with::<BitRows>(&input));
+  }
+
+  #[test]
+  fn flat_slopes() {
+    let map: Map = Map::parse(EXAMPLE).unwrap();
+    assert_eq!(0, map.count_trees_along_slope((3, 0)));
+    assert_eq!(vec![7, 0], map.count_trees_along_slopes(&[(3, 1), (3, 0)]));
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = problem("..#\n.#.\n.O.\n").unwrap_err();
+    assert_eq!((3, 2), (err.line, err.column));
+  }
 }
@@ -0,0 +1,131 @@
+use super::{square, Trees};
+use crate::grid;
+use crate::parse::ParseError;
+
+/// Trees stored as one bit per square, each row starting on a fresh `u64`,
+/// so a map takes an eighth of the memory a `Grid<bool>` does and whole
+/// words of squares can be looked at together.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BitRows {
+  width: usize,
+  height: usize,
+  words_per_row: usize,
+  words: Vec<u64>,
+}
+impl BitRows {
+  pub fn new(width: usize, height: usize) -> Self {
+    let words_per_row = width.div_ceil(64);
+    Self {
+      width,
+      height,
+      words_per_row,
+      words: vec![0; words_per_row * height],
+    }
+  }
+
+  pub fn set(&mut self, x: usize, y: usize, tree: bool) {
+    let (word, bit) = self.locate(x, y);
+    if tree {
+      self.words[word] |= 1 << bit;
+    } else {
+      self.words[word] &= !(1 << bit);
+    }
+  }
+
+  /// The words holding row `y`, with square `x` at bit `x % 64` of word
+  /// `x / 64`. Bits past the end of the row are always 0.
+  pub fn row(&self, y: usize) -> &[u64] {
+    &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+  }
+
+  fn locate(&self, x: usize, y: usize) -> (usize, usize) {
+    debug_assert!(x < self.width && y < self.height);
+    (y * self.words_per_row + x / 64, x % 64)
+  }
+}
+impl Trees for BitRows {
+  /// Packs each row into bits as it's read, so there's never more than one
+  /// row of `bool`s in memory.
+  fn parse(input: &str) -> Result<Self, ParseError> {
+    let mut words = Vec::new();
+    let (width, height) = grid::parse_rows(input, square, |row| {
+      for chunk in row.chunks(64) {
+        let word = chunk
+          .iter()
+          .enumerate()
+          .filter(|(_, tree)| **tree)
+          .fold(0u64, |word, (bit, _)| word | 1 << bit);
+        words.push(word);
+      }
+    })?;
+    Ok(Self {
+      width,
+      height,
+      words_per_row: width.div_ceil(64),
+      words,
+    })
+  }
+
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+
+  fn is_tree(&self, x: usize, y: usize) -> bool {
+    let (word, bit) = self.locate(x, y);
+    self.words[word] & (1 << bit) != 0
+  }
+
+  fn count(&self) -> usize {
+    self.words.iter().map(|w| w.count_ones() as usize).sum()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::super::{Map, EXAMPLE};
+  use super::*;
+  use crate::generate::{self, Rng};
+
+  #[test]
+  fn same_as_grid() {
+    let rng = &mut Rng::new(3);
+    let forest = generate::day_3(rng, 50).text;
+    let wide: String = forest.lines().map(|line| line.repeat(5) + "\n").collect();
+    for text in [EXAMPLE, &forest, &wide].iter() {
+      let grid: Map = Map::parse(text).unwrap();
+      let bits: Map<BitRows> = Map::parse(text).unwrap();
+      assert_eq!((grid.width(), grid.height()), (bits.width(), bits.height()));
+      assert_eq!(grid.count_trees(), bits.count_trees());
+      assert_eq!(grid.rank_slopes(40, 3), bits.rank_slopes(40, 3));
+    }
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = Map::<BitRows>::parse("..#\n.#.\n.O.\n").unwrap_err();
+    assert_eq!((3, 2), (err.line, err.column));
+    let err = Map::<BitRows>::parse("..#\n.#\n").unwrap_err();
+    assert_eq!((2, 1), (err.line, err.column));
+    let empty = Map::<BitRows>::parse("").unwrap();
+    assert_eq!((0, 0), (empty.width(), empty.height()));
+  }
+
+  #[test]
+  fn rows_are_packed() {
+    let mut bits = BitRows::new(70, 2);
+    bits.set(0, 1, true);
+    bits.set(69, 1, true);
+    bits.set(3, 0, true);
+    bits.set(3, 0, false);
+    assert_eq!(&[0, 0], bits.row(0));
+    assert_eq!(&[1, 1 << 5], bits.row(1));
+    assert_eq!(2, bits.count());
+    let text = format!("{}\n#{}#\n", ".".repeat(70), ".".repeat(68));
+    let parsed = BitRows::parse(&text).unwrap();
+    assert_eq!(bits, parsed);
+  }
+}
@@ -0,0 +1,130 @@
+//! Draws a map with the paths of one or more slopes over it, the way the
+//! puzzle does: `O` where a path crosses open ground and `X` where it hits a
+//! tree.
+
+use super::{Map, Trees};
+use crate::grid::Point;
+use colored::{Color, Colorize};
+use std::collections::HashMap;
+
+/// The colors paths are drawn in, in the order their slopes are given.
+/// When there are more slopes than colors, they're reused.
+const PATH_COLORS: [Color; 6] = [
+  Color::Red,
+  Color::Green,
+  Color::Yellow,
+  Color::Blue,
+  Color::Magenta,
+  Color::Cyan,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Style {
+  Plain,
+  /// Each slope's path in its own color, with ANSI escapes.
+  Colored,
+}
+
+impl<T: Trees> Map<T> {
+  /// Draws the map, repeated to the right as many times as the furthest
+  /// path needs. Where paths cross, the first slope's is drawn. A slope that
+  /// doesn't go down has no path, just as it hits no trees.
+  pub fn render(&self, slopes: &[Point], style: Style) -> String {
+    let mut paths: HashMap<Point, usize> = HashMap::new();
+    for (idx, (dx, dy)) in slopes.iter().enumerate() {
+      if *dy == 0 {
+        continue;
+      }
+      for (step, y) in (0..self.height()).step_by(*dy).enumerate() {
+        paths.entry((step * dx, y)).or_insert(idx);
+      }
+    }
+    let furthest = paths.keys().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let width = self.width().max(1);
+    let repeats = furthest.div_ceil(width).max(1);
+
+    let mut text = String::new();
+    for y in 0..self.height() {
+      for x in 0..width * repeats {
+        let tree = self.is_tree((x, y));
+        let square = match paths.get(&(x, y)) {
+          None => (if tree { "#" } else { "." }).to_string(),
+          Some(idx) => {
+            let marker = if tree { "X" } else { "O" };
+            match style {
+              Style::Plain => marker.to_string(),
+              Style::Colored => marker
+                .color(PATH_COLORS[idx % PATH_COLORS.len()])
+                .bold()
+                .to_string(),
+            }
+          }
+        };
+        text.push_str(&square);
+      }
+      text.push('\n');
+    }
+    text
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::super::EXAMPLE;
+  use super::*;
+
+  #[test]
+  fn plain() {
+    let map: Map = Map::parse(EXAMPLE).unwrap();
+    assert_eq!(
+      "\
+O.##.........##.........##.......
+#..O#...#..#...#...#..#...#...#..
+.#....X..#..#....#..#..#....#..#.
+..#.#...#O#..#.#...#.#..#.#...#.#
+.#...##..#..X...##..#..#...##..#.
+..#.##.......#.X#.......#.##.....
+.#.#.#....#.#.#.#.O..#.#.#.#....#
+.#........#.#........X.#........#
+#.##...#...#.##...#...#.X#...#...
+#...##....##...##....##...#X....#
+.#..#...#.#.#..#...#.#.#..#...X.#
+",
+      map.render(&[(3, 1)], Style::Plain)
+    );
+  }
+
+  #[test]
+  fn several_slopes() {
+    let map: Map = Map::parse("....\n....\n....\n").unwrap();
+    assert_eq!(
+      "O...\nOO..\nO.O.\n",
+      map.render(&[(0, 1), (1, 1)], Style::Plain)
+    );
+    assert_eq!("O...\n....\n..O.\n", map.render(&[(2, 2)], Style::Plain));
+    assert_eq!(
+      "O.......\n...O....\n......O.\n",
+      map.render(&[(3, 1)], Style::Plain)
+    );
+  }
+
+  #[test]
+  fn flat_slopes() {
+    let map: Map = Map::parse("...\n...\n...\n").unwrap();
+    assert_eq!("...\n...\n...\n", map.render(&[(1, 0)], Style::Plain));
+    assert_eq!(
+      "O..\n.O.\n..O\n",
+      map.render(&[(1, 0), (1, 1)], Style::Plain)
+    );
+  }
+
+  #[test]
+  fn colored() {
+    colored::control::set_override(true);
+    let map: Map = Map::parse("#.\n.#\n").unwrap();
+    let rendered = map.render(&[(1, 1), (0, 1)], Style::Colored);
+    colored::control::unset_override();
+    assert!(rendered.contains(&"X".red().bold().to_string()));
+    assert!(rendered.contains(&"O".green().bold().to_string()));
+  }
+}
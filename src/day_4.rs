@@ -1,6 +1,18 @@
+use crate::parse::{self, ParseError, ReadError};
+use crate::{Example, Solution};
 use enum_map::EnumMap;
+use schema::{FailureKind, Schema, ValidationFailure};
 use PassportField::*;
 
+pub mod exchange;
+pub mod height;
+pub mod passport;
+pub mod repair;
+pub mod schema;
+pub mod stream;
+
+pub use passport::Passport;
+
 #[derive(enum_map::Enum, Copy, Clone, PartialEq, Eq, Debug)]
 enum PassportField {
   BirthYear,
@@ -13,141 +25,246 @@ enum PassportField {
   CountryId,
 }
 impl PassportField {
-  fn validate(&self, value: Option<&str>) -> bool {
-    match (self, value) {
-      (CountryId, _) => true,
-      (_, None) => false,
-      (_, Some(s)) => self.validate_value(s),
-    }
+  fn from_key(key: &str) -> Option<Self> {
+    Some(match key {
+      "byr" => BirthYear,
+      "iyr" => IssueYear,
+      "eyr" => ExpirationYear,
+      "hgt" => Height,
+      "hcl" => HairColor,
+      "ecl" => EyeColor,
+      "pid" => PassportId,
+      "cid" => CountryId,
+      _ => return None,
+    })
   }
 
-  fn validate_value(&self, value: &str) -> bool {
+  fn key(&self) -> &'static str {
     match self {
-      BirthYear => value
-        .parse()
-        .map(|v: u16| v >= 1920 && v <= 2002)
-        .unwrap_or(false),
-      IssueYear => value
-        .parse()
-        .map(|v: u16| v >= 2010 && v <= 2020)
-        .unwrap_or(false),
-      ExpirationYear => value
-        .parse()
-        .map(|v: u16| v >= 2020 && v <= 2030)
-        .unwrap_or(false),
-      Height => {
-        let in_cm = match &value[value.len() - 2..] {
-          "cm" => true,
-          "in" => false,
-          _ => return false,
-        };
-
-        value[0..value.len() - 2]
-          .parse()
-          .map(|v: u16| {
-            if in_cm {
-              v >= 150 && v <= 193
-            } else {
-              v >= 59 && v <= 76
-            }
-          })
-          .unwrap_or(false)
-      }
-      HairColor => {
-        if value.len() != 7 {
-          return false;
-        }
-        let mut chars = value.chars();
-        if Some('#') != chars.next() {
-          return false;
-        }
-        chars.all(|c| c.is_digit(16))
-      }
-      EyeColor => match value {
-        "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-        _ => false,
-      },
-      PassportId => value.len() == 9 && value.chars().all(|c| c.is_ascii_digit()),
-      CountryId => true,
+      BirthYear => "byr",
+      IssueYear => "iyr",
+      ExpirationYear => "eyr",
+      Height => "hgt",
+      HairColor => "hcl",
+      EyeColor => "ecl",
+      PassportId => "pid",
+      CountryId => "cid",
     }
   }
 }
-const PASSPORT_FIELDS: [PassportField; 8] = [
-  BirthYear,
-  IssueYear,
-  ExpirationYear,
-  Height,
-  HairColor,
-  EyeColor,
-  PassportId,
-  CountryId,
-];
-
-struct PassportCandidate<'a> {
+/// A record from the input, which may or may not be a valid passport.
+pub struct PassportCandidate<'a> {
   fields: EnumMap<PassportField, Option<&'a str>>,
+  /// Fields the puzzle doesn't describe, in the order they appear.
+  extras: Vec<(&'a str, &'a str)>,
+  /// Fields that appear again after their first value, which is the one
+  /// that's kept.
+  duplicates: Vec<(&'a str, &'a str)>,
 }
 impl<'a> PassportCandidate<'a> {
-  fn parse(str: &'a str) -> Self {
-    let mut fields = EnumMap::new();
-    let key_value_pairs = str.split(|c| c == ' ' || c == '\n').map(|key_value| {
-      let key = match &key_value[0..4] {
-        "byr:" => BirthYear,
-        "iyr:" => IssueYear,
-        "eyr:" => ExpirationYear,
-        "hgt:" => Height,
-        "hcl:" => HairColor,
-        "ecl:" => EyeColor,
-        "pid:" => PassportId,
-        "cid:" => CountryId,
-        unkn => panic!("Unknown field: {}", unkn),
-      };
-      (key, &key_value[4..])
-    });
-    for (key, value) in key_value_pairs {
-      fields[key] = Some(value);
+  /// Fields may be separated by any whitespace, including tabs and the
+  /// `\r` of Windows line endings.
+  pub fn parse(record: &'a str) -> Result<Self, ParseError> {
+    let mut candidate = Self::from_fields(std::iter::empty());
+    for (line_idx, line) in record.lines().enumerate() {
+      for (column, key_value) in parse::words(line) {
+        let (key, value) =
+          Self::parse_field(key_value).map_err(|e| e.offset(line_idx, column - 1))?;
+        candidate.insert(key, value);
+      }
     }
-    Self { fields }
+    Ok(candidate)
   }
 
-  fn is_valid(&self) -> bool {
-    for field in PASSPORT_FIELDS.iter() {
-      if let CountryId = field {
-        continue;
-      }
-      if self.fields[*field].is_none() {
-        return false;
-      }
+  /// A candidate with `fields`, as though they'd been read in that order.
+  pub fn from_fields(fields: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+    let mut candidate = Self {
+      fields: EnumMap::default(),
+      extras: Vec::new(),
+      duplicates: Vec::new(),
+    };
+    for (key, value) in fields {
+      candidate.insert(key, value);
     }
-    return true;
+    candidate
   }
 
-  fn extended_validation(&self) -> bool {
-    PASSPORT_FIELDS.iter().all(|field| {
-      let result = field.validate(self.fields[*field]);
-      if !result {
-        println!("{:?} is an invalid {:?}", field, self.fields[*field]);
-      }
-      result
-    })
+  fn insert(&mut self, key: &'a str, value: &'a str) {
+    if self.get(key).is_some() {
+      self.duplicates.push((key, value));
+    } else if let Some(field) = PassportField::from_key(key) {
+      self.fields[field] = Some(value);
+    } else {
+      self.extras.push((key, value));
+    }
+  }
+
+  fn parse_field(key_value: &'a str) -> Result<(&'a str, &'a str), ParseError> {
+    key_value
+      .split_once(':')
+      .ok_or_else(|| ParseError::at(1, format!("expected key:value, got {:?}", key_value)))
+  }
+
+  /// The value of the field with `key`, if the record has one.
+  pub fn get(&self, key: &str) -> Option<&'a str> {
+    match PassportField::from_key(key) {
+      Some(field) => self.fields[field],
+      None => self
+        .extras
+        .iter()
+        .find(|(extra, _)| *extra == key)
+        .map(|(_, value)| *value),
+    }
+  }
+
+  /// The fields the puzzle describes that the record has, in the puzzle's
+  /// order.
+  pub fn fields(&self) -> impl Iterator<Item = (&'static str, &'a str)> + '_ {
+    self
+      .fields
+      .iter()
+      .filter_map(|(field, value)| value.map(|value| (field.key(), value)))
+  }
+
+  pub fn extras(&self) -> &[(&'a str, &'a str)] {
+    &self.extras
+  }
+
+  pub fn duplicates(&self) -> &[(&'a str, &'a str)] {
+    &self.duplicates
+  }
+
+  /// Whether every field that `schema` requires is present.
+  pub fn has_required(&self, schema: &Schema) -> bool {
+    schema.required().all(|rule| self.get(&rule.key).is_some())
+  }
+
+  /// Whether every field follows `schema`'s rules, and none is repeated.
+  pub fn validate(&self, schema: &Schema) -> bool {
+    self.duplicates.is_empty()
+      && schema
+        .fields
+        .iter()
+        .all(|rule| rule.allows(self.get(&rule.key)))
+  }
+
+  /// Every field that breaks `schema`'s rules, and how, in the schema's
+  /// order, followed by any repeated fields. Empty for a valid passport.
+  pub fn extended_validation(&self, schema: &Schema) -> Vec<ValidationFailure> {
+    let broken = schema
+      .fields
+      .iter()
+      .filter_map(|rule| rule.check(self.get(&rule.key)).err());
+    let repeated = self
+      .duplicates
+      .iter()
+      .map(|(key, value)| ValidationFailure {
+        field: key.to_string(),
+        value: Some(value.to_string()),
+        kind: FailureKind::Duplicate {
+          first: self.get(key).unwrap_or_default().to_string(),
+        },
+      });
+    broken.chain(repeated).collect()
+  }
+}
+
+fn parse_candidates(input: &str) -> Result<Vec<PassportCandidate<'_>>, ParseError> {
+  parse::records(input)
+    .into_iter()
+    .map(|(line, record)| PassportCandidate::parse(record).map_err(|e| e.offset(line, 0)))
+    .collect()
+}
+
+pub fn problem(input: &str) -> Result<usize, ParseError> {
+  count_complete(input, Schema::puzzle())
+}
+
+pub fn problem_part_2(input: &str) -> Result<usize, ParseError> {
+  count_valid(input, Schema::puzzle())
+}
+
+/// Counts the records with every field that `schema` requires.
+pub fn count_complete(input: &str, schema: &Schema) -> Result<usize, ParseError> {
+  Ok(
+    parse_candidates(input)?
+      .iter()
+      .filter(|pc| pc.has_required(schema))
+      .count(),
+  )
+}
+
+/// Counts the records that follow all of `schema`'s rules.
+pub fn count_valid(input: &str, schema: &Schema) -> Result<usize, ParseError> {
+  Ok(
+    parse_candidates(input)?
+      .iter()
+      .filter(|pc| pc.validate(schema))
+      .count(),
+  )
+}
+
+pub const EXAMPLE: &str = "\
+ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = Schema;
+  type Answer = usize;
+  const DAY: u8 = 4;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, schema: Schema) -> Result<usize, ParseError> {
+    count_complete(text, &schema)
   }
 }
 
-pub fn problem(input: &str) -> usize {
-  input
-    .trim_end()
-    .split("\n\n")
-    .map(|c| PassportCandidate::parse(c))
-    .filter(|pc| pc.is_valid())
-    .count()
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = Schema;
+  type Answer = usize;
+  const DAY: u8 = 4;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, schema: Schema) -> Result<usize, ParseError> {
+    count_valid(text, &schema)
+  }
 }
 
-pub fn problem_part_2(input: &str) -> usize {
-  input
-    .trim_end()
-    .split("\n\n")
-    .map(|c| PassportCandidate::parse(c))
-    .filter(|pc| pc.extended_validation())
-    .count()
+pub struct Part2Streaming;
+impl Solution for Part2Streaming {
+  type Input = Schema;
+  type Answer = usize;
+  const DAY: u8 = 4;
+  const PART: u8 = 2;
+  const NAME: &'static str = "streaming";
+  const EXAMPLE: Option<Example> = Part2::EXAMPLE;
+
+  fn solve(text: &str, schema: Schema) -> Result<usize, ParseError> {
+    let workers = stream::default_workers();
+    match stream::validate_stream(text.as_bytes(), &schema, workers, |_| {}) {
+      Ok(tally) => Ok(tally.valid),
+      Err(ReadError::Parse(e)) => Err(e),
+      // Reading a `str` can only fail on a line that isn't UTF-8, which is
+      // reported as a parse error on that line.
+      Err(ReadError::Io(e)) => unreachable!("reading from memory failed: {}", e),
+    }
+  }
 }
 
 #[cfg(test)]
@@ -156,18 +273,19 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(2, problem(EXAMPLE));
+    assert_eq!(Ok(2), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(247, problem(MY_INPUT));
+    let input = my_input!(4);
+    assert_eq!(Ok(247), problem(&input));
   }
 
   #[test]
   fn part_2_negative_examples() {
     assert_eq!(
-      0,
+      Ok(0),
       problem_part_2(
         "\
 eyr:1972 cid:100
@@ -191,7 +309,7 @@ pid:3556412378 byr:2007\
   #[test]
   fn part_2_positive_examples() {
     assert_eq!(
-      4,
+      Ok(4),
       problem_part_2(
         "\
 pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
@@ -213,23 +331,85 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719\
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(145, problem_part_2(MY_INPUT));
+    let input = my_input!(4);
+    assert_eq!(Ok(145), problem_part_2(&input));
   }
 
-  static EXAMPLE: &'static str = "\
-ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
-byr:1937 iyr:2017 cid:147 hgt:183cm
+  #[test]
+  fn failures() {
+    let candidates = parse_candidates(EXAMPLE).unwrap();
+    let failures: Vec<Vec<String>> = candidates
+      .iter()
+      .map(|pc| {
+        pc.extended_validation(Schema::puzzle())
+          .iter()
+          .map(|failure| failure.to_string())
+          .collect()
+      })
+      .collect();
+    assert_eq!(
+      vec![
+        vec![],
+        vec!["hgt is missing".to_string()],
+        vec![],
+        vec!["byr is missing".to_string()],
+      ],
+      failures
+    );
+  }
 
-iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
-hcl:#cfa07d byr:1929
+  #[test]
+  fn custom_schema() {
+    let schema = Schema::parse(&schema::PUZZLE.replace("1920-2002", "1935-2002")).unwrap();
+    assert_eq!(Ok(1), count_valid(EXAMPLE, &schema));
+    let failures = parse_candidates(EXAMPLE).unwrap()[2].extended_validation(&schema);
+    assert_eq!(
+      vec!["byr: \"1931\" is outside 1935-2002".to_string()],
+      failures.iter().map(|f| f.to_string()).collect::<Vec<_>>()
+    );
+    let schema = Schema::parse("cid required\n").unwrap();
+    assert_eq!(Ok(2), count_complete(EXAMPLE, &schema));
+    assert_eq!(Ok(4), count_valid(EXAMPLE, &Schema::parse("").unwrap()));
+  }
 
-hcl:#ae17e1 iyr:2013
-eyr:2024
-ecl:brn pid:760753108 byr:1931
-hgt:179cm
+  #[test]
+  fn odd_whitespace() {
+    let windows = EXAMPLE.replace('\n', "\r\n");
+    assert_eq!(Ok(2), problem(&windows));
+    assert_eq!(Ok(2), problem_part_2(&windows));
+    let spaced = EXAMPLE.replace(' ', "\t ").replace("\n\n", "\n  \n\t\n\n");
+    assert_eq!(Ok(2), problem(&spaced));
+    assert_eq!(Ok(2), problem_part_2(&spaced));
+  }
 
-hcl:#cfa07d eyr:2025 pid:166559648
-iyr:2011 ecl:brn hgt:59in\
-  ";
-  static MY_INPUT: &'static str = include_str!("day_4_input.txt");
+  #[test]
+  fn unknown_and_repeated_fields() {
+    let record = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd zzz:1\n\
+                  byr:1937 iyr:2017 cid:147 hgt:183cm byr:1950 zzz:2";
+    let candidate = PassportCandidate::parse(record).unwrap();
+    assert_eq!(&[("zzz", "1")], candidate.extras());
+    assert_eq!(&[("byr", "1950"), ("zzz", "2")], candidate.duplicates());
+    assert_eq!(Some("1937"), candidate.get("byr"));
+    assert_eq!(Some("1"), candidate.get("zzz"));
+    assert!(candidate.has_required(Schema::puzzle()));
+    assert!(!candidate.validate(Schema::puzzle()));
+    let failures: Vec<String> = candidate
+      .extended_validation(Schema::puzzle())
+      .iter()
+      .map(|f| f.to_string())
+      .collect();
+    assert_eq!(
+      vec![
+        "byr: \"1950\" repeats a field already set to \"1937\"".to_string(),
+        "zzz: \"2\" repeats a field already set to \"1\"".to_string(),
+      ],
+      failures
+    );
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = problem("byr:1937 iyr").unwrap_err();
+    assert_eq!((1, 10), (err.line, err.column));
+  }
 }
@@ -0,0 +1,406 @@
+//! Moves batches of passport records between the puzzle's format, JSON lines
+//! and CSV, without losing anything a record holds.
+
+use super::{parse_candidates, PassportCandidate};
+use crate::parse::{ParseError, ReadError};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, Read, Write};
+
+/// A passport record that owns its values, so it can be read from or written
+/// to any format. Turn it into a `PassportCandidate` to validate it.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct PassportRecord {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub byr: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub iyr: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub eyr: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hgt: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hcl: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub ecl: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub pid: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cid: Option<String>,
+  /// Fields the puzzle doesn't describe, then repeats of earlier fields, each
+  /// in the order they were read.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub extras: Vec<(String, String)>,
+}
+impl PassportRecord {
+  pub fn from_candidate(candidate: &PassportCandidate) -> Self {
+    let mut record = Self::default();
+    for (key, value) in candidate.fields() {
+      *record.field_mut(key).unwrap() = Some(value.to_string());
+    }
+    let mut extras: Vec<_> = candidate.extras().iter().collect();
+    extras.extend(candidate.duplicates());
+    record.extras = extras
+      .into_iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect();
+    record
+  }
+
+  pub fn candidate(&self) -> PassportCandidate<'_> {
+    PassportCandidate::from_fields(self.pairs())
+  }
+
+  /// Every field, the ones the puzzle describes first.
+  pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+    let known = [
+      ("byr", &self.byr),
+      ("iyr", &self.iyr),
+      ("eyr", &self.eyr),
+      ("hgt", &self.hgt),
+      ("hcl", &self.hcl),
+      ("ecl", &self.ecl),
+      ("pid", &self.pid),
+      ("cid", &self.cid),
+    ];
+    let known: Vec<_> = known
+      .iter()
+      .filter_map(|(key, value)| value.as_deref().map(|value| (*key, value)))
+      .collect();
+    let extras = self
+      .extras
+      .iter()
+      .map(|(key, value)| (key.as_str(), value.as_str()));
+    known.into_iter().chain(extras)
+  }
+
+  pub(crate) fn field_mut(&mut self, key: &str) -> Option<&mut Option<String>> {
+    Some(match key {
+      "byr" => &mut self.byr,
+      "iyr" => &mut self.iyr,
+      "eyr" => &mut self.eyr,
+      "hgt" => &mut self.hgt,
+      "hcl" => &mut self.hcl,
+      "ecl" => &mut self.ecl,
+      "pid" => &mut self.pid,
+      "cid" => &mut self.cid,
+      _ => return None,
+    })
+  }
+}
+/// The record in the puzzle's format, on one line.
+impl Display for PassportRecord {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (idx, (key, value)) in self.pairs().enumerate() {
+      if idx > 0 {
+        f.write_str(" ")?;
+      }
+      write!(f, "{}:{}", key, value)?;
+    }
+    Ok(())
+  }
+}
+
+pub fn read_text(input: &str) -> Result<Vec<PassportRecord>, ParseError> {
+  Ok(
+    parse_candidates(input)?
+      .iter()
+      .map(PassportRecord::from_candidate)
+      .collect(),
+  )
+}
+
+/// Writes one record per line, with blank lines between them.
+pub fn write_text(records: &[PassportRecord], mut writer: impl Write) -> io::Result<()> {
+  for (idx, record) in records.iter().enumerate() {
+    if idx > 0 {
+      writeln!(writer)?;
+    }
+    writeln!(writer, "{}", record)?;
+  }
+  Ok(())
+}
+
+/// Reads one JSON object per line. Blank lines are skipped.
+pub fn read_json_lines(reader: impl BufRead) -> Result<Vec<PassportRecord>, ReadError> {
+  let mut records = Vec::new();
+  for (idx, line) in reader.lines().enumerate() {
+    let line = line?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    let record = serde_json::from_str(&line)
+      .map_err(|e| ParseError::new(idx + 1, e.column().max(1), e.to_string()))?;
+    records.push(record);
+  }
+  Ok(records)
+}
+
+pub fn write_json_lines(
+  records: &[PassportRecord],
+  mut writer: impl Write,
+) -> serde_json::Result<()> {
+  for record in records {
+    serde_json::to_writer(&mut writer, record)?;
+    writeln!(writer).map_err(serde_json::Error::io)?;
+  }
+  Ok(())
+}
+
+/// A record as a CSV row. CSV can't tell an empty value from a missing one,
+/// so fields with empty values are listed in `extras` instead.
+#[derive(Serialize, Deserialize)]
+struct CsvRow {
+  byr: Option<String>,
+  iyr: Option<String>,
+  eyr: Option<String>,
+  hgt: Option<String>,
+  hcl: Option<String>,
+  ecl: Option<String>,
+  pid: Option<String>,
+  cid: Option<String>,
+  /// A `CsvExtras` as JSON, or nothing when it has nothing in it.
+  extras: String,
+}
+/// What a record holds besides its fields' values, which CSV columns can't.
+#[derive(Default, Serialize, Deserialize)]
+struct CsvExtras {
+  /// Fields that are there, but with empty values.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  empty: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  extras: Vec<(String, String)>,
+}
+impl From<&PassportRecord> for CsvRow {
+  fn from(record: &PassportRecord) -> Self {
+    let mut empty = Vec::new();
+    let mut column = |key: &str, value: &Option<String>| match value.as_deref() {
+      Some("") => {
+        empty.push(key.to_string());
+        None
+      }
+      value => value.map(str::to_string),
+    };
+    let mut row = CsvRow {
+      byr: column("byr", &record.byr),
+      iyr: column("iyr", &record.iyr),
+      eyr: column("eyr", &record.eyr),
+      hgt: column("hgt", &record.hgt),
+      hcl: column("hcl", &record.hcl),
+      ecl: column("ecl", &record.ecl),
+      pid: column("pid", &record.pid),
+      cid: column("cid", &record.cid),
+      extras: String::new(),
+    };
+    if !empty.is_empty() || !record.extras.is_empty() {
+      let extras = CsvExtras {
+        empty,
+        extras: record.extras.clone(),
+      };
+      row.extras = serde_json::to_string(&extras).expect("pairs of strings are always JSON");
+    }
+    row
+  }
+}
+impl CsvRow {
+  fn into_record(self, line: usize) -> Result<PassportRecord, ParseError> {
+    let extras: CsvExtras = if self.extras.is_empty() {
+      CsvExtras::default()
+    } else {
+      serde_json::from_str(&self.extras)
+        .map_err(|e| ParseError::new(line, e.column().max(1), format!("bad extras: {}", e)))?
+    };
+    let mut record = PassportRecord {
+      byr: self.byr,
+      iyr: self.iyr,
+      eyr: self.eyr,
+      hgt: self.hgt,
+      hcl: self.hcl,
+      ecl: self.ecl,
+      pid: self.pid,
+      cid: self.cid,
+      extras: extras.extras,
+    };
+    for key in extras.empty {
+      match record.field_mut(&key) {
+        Some(field @ None) => *field = Some(String::new()),
+        _ => {
+          return Err(ParseError::new(
+            line,
+            1,
+            format!("{:?} can't be empty and have a value", key),
+          ))
+        }
+      }
+    }
+    Ok(record)
+  }
+}
+
+/// Reads CSV with a header row, and a column for each field the puzzle
+/// describes followed by an `extras` column, as `write_csv` writes it.
+pub fn read_csv(reader: impl Read) -> Result<Vec<PassportRecord>, ReadError> {
+  let mut reader = csv::Reader::from_reader(reader);
+  let headers = reader.headers().map_err(csv_error)?.clone();
+  let mut row = csv::StringRecord::new();
+  let mut records = Vec::new();
+  while reader.read_record(&mut row).map_err(csv_error)? {
+    let line = row
+      .position()
+      .map_or(1, |position| position.line() as usize);
+    let parsed: CsvRow = row.deserialize(Some(&headers)).map_err(csv_error)?;
+    records.push(parsed.into_record(line)?);
+  }
+  Ok(records)
+}
+
+fn csv_error(e: csv::Error) -> ReadError {
+  let line = e.position().map_or(1, |position| position.line() as usize);
+  let message = e.to_string();
+  match e.into_kind() {
+    csv::ErrorKind::Io(e) => ReadError::Io(e),
+    _ => ReadError::Parse(ParseError::new(line, 1, message)),
+  }
+}
+
+pub fn write_csv(records: &[PassportRecord], writer: impl Write) -> csv::Result<()> {
+  let mut writer = csv::Writer::from_writer(writer);
+  for record in records {
+    writer.serialize(CsvRow::from(record))?;
+  }
+  writer.flush()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::super::schema::Schema;
+  use super::super::EXAMPLE;
+  use super::*;
+
+  const ODD_RECORDS: &str = "\
+byr:1937 zzz:1 byr:1950 iyr: zzz:a,\"b\"
+hgt:183cm cid:ü
+
+ecl:gry
+";
+
+  fn round_trips(records: &[PassportRecord]) {
+    let mut text = Vec::new();
+    write_text(records, &mut text).unwrap();
+    assert_eq!(
+      records,
+      &read_text(&String::from_utf8(text).unwrap()).unwrap()[..]
+    );
+
+    let mut json = Vec::new();
+    write_json_lines(records, &mut json).unwrap();
+    assert_eq!(records, &read_json_lines(&json[..]).unwrap()[..]);
+
+    let mut csv = Vec::new();
+    write_csv(records, &mut csv).unwrap();
+    assert_eq!(records, &read_csv(&csv[..]).unwrap()[..]);
+  }
+
+  #[test]
+  fn nothing_is_lost() {
+    round_trips(&read_text(EXAMPLE).unwrap());
+    let records = read_text(ODD_RECORDS).unwrap();
+    assert_eq!(Some(""), records[0].iyr.as_deref());
+    assert_eq!(
+      vec![
+        ("zzz".to_string(), "1".to_string()),
+        ("byr".to_string(), "1950".to_string()),
+        ("zzz".to_string(), "a,\"b\"".to_string()),
+      ],
+      records[0].extras
+    );
+    round_trips(&records);
+  }
+
+  #[test]
+  fn formats() {
+    let records = read_text("byr:1937 zzz:1\n\ncid:2").unwrap();
+    let mut json = Vec::new();
+    write_json_lines(&records, &mut json).unwrap();
+    assert_eq!(
+      "{\"byr\":\"1937\",\"extras\":[[\"zzz\",\"1\"]]}\n{\"cid\":\"2\"}\n",
+      String::from_utf8(json).unwrap()
+    );
+    let mut csv = Vec::new();
+    write_csv(&records, &mut csv).unwrap();
+    assert_eq!(
+      "byr,iyr,eyr,hgt,hcl,ecl,pid,cid,extras\n\
+       1937,,,,,,,,\"{\"\"extras\"\":[[\"\"zzz\"\",\"\"1\"\"]]}\"\n\
+       ,,,,,,,2,\n",
+      String::from_utf8(csv).unwrap()
+    );
+  }
+
+  #[test]
+  fn from_json() {
+    let json = r#"{"byr":"1937","extras":[["zzz","a b:c"],["yyy",""],["byr","x y"]]}
+{"iyr":"","hgt":"1 m","extras":[["ecl",""],["ecl:x",":"]]}
+{"extras":[["pid","1"]]}
+"#;
+    let records = read_json_lines(json.as_bytes()).unwrap();
+    assert_eq!("a b:c", records[0].extras[0].1);
+    let mut csv = Vec::new();
+    write_csv(&records, &mut csv).unwrap();
+    assert_eq!(records, read_csv(&csv[..]).unwrap());
+  }
+
+  #[test]
+  fn validates_the_same_from_every_format() {
+    let input = my_input!(4);
+    let records = read_text(&input).unwrap();
+    let count = |records: &[PassportRecord]| {
+      let candidates: Vec<_> = records.iter().map(PassportRecord::candidate).collect();
+      let failures: Vec<_> = candidates
+        .iter()
+        .map(|pc| pc.extended_validation(Schema::puzzle()))
+        .collect();
+      let valid = candidates
+        .iter()
+        .filter(|pc| pc.validate(Schema::puzzle()))
+        .count();
+      (valid, failures)
+    };
+    let (valid, failures) = count(&records);
+    assert_eq!(145, valid);
+
+    let mut json = Vec::new();
+    write_json_lines(&records, &mut json).unwrap();
+    assert_eq!(
+      (valid, failures.clone()),
+      count(&read_json_lines(&json[..]).unwrap())
+    );
+
+    let mut csv = Vec::new();
+    write_csv(&records, &mut csv).unwrap();
+    assert_eq!((valid, failures), count(&read_csv(&csv[..]).unwrap()));
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = read_json_lines(&b"{\"byr\":\"1937\"}\n\n{\"byr\": 1937}\n"[..]).unwrap_err();
+    match err {
+      ReadError::Parse(e) => assert_eq!(3, e.line),
+      e => panic!("{:?}", e),
+    }
+    let header = "byr,iyr,eyr,hgt,hcl,ecl,pid,cid,extras\n";
+    let err = read_csv(format!("{},,,,,,,,oops\n", header).as_bytes()).unwrap_err();
+    match err {
+      ReadError::Parse(e) => assert_eq!((2, 1), (e.line, e.column)),
+      e => panic!("{:?}", e),
+    }
+    // A field can't be both empty and 1937.
+    let row = "1937,,,,,,,,\"{\"\"empty\"\":[\"\"byr\"\"]}\"\n";
+    let err = read_csv(format!("{}{}", header, row).as_bytes()).unwrap_err();
+    match err {
+      ReadError::Parse(e) => assert_eq!(2, e.line),
+      e => panic!("{:?}", e),
+    }
+    assert!(read_csv(&b"byr\n1937\n"[..]).is_err());
+  }
+}
@@ -0,0 +1,254 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A length, kept in micrometers so that every unit converts exactly, along
+/// with the unit it was written in.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Height {
+  micrometers: i64,
+  unit: HeightUnit,
+}
+impl Height {
+  pub fn from_micrometers(micrometers: i64, unit: HeightUnit) -> Self {
+    Self { micrometers, unit }
+  }
+
+  /// `amount` of `unit`, or None if that's too long to count in
+  /// micrometers. Feet and inches are counted in feet.
+  pub fn new(amount: i64, unit: HeightUnit) -> Option<Self> {
+    let micrometers = amount.checked_mul(unit.micrometers())?;
+    Some(Self::from_micrometers(micrometers, unit))
+  }
+
+  pub fn micrometers(&self) -> i64 {
+    self.micrometers
+  }
+
+  pub fn centimeters(&self) -> f64 {
+    self.micrometers as f64 / HeightUnit::Centimeters.micrometers() as f64
+  }
+
+  pub fn unit(&self) -> HeightUnit {
+    self.unit
+  }
+
+  /// The same length, to be written in `unit`.
+  pub fn in_unit(self, unit: HeightUnit) -> Self {
+    Self { unit, ..self }
+  }
+
+  /// Whether this is the same length as `other`, whatever their units.
+  pub fn same_length(&self, other: &Height) -> bool {
+    self.micrometers == other.micrometers
+  }
+}
+/// Reads a number of `mm`, `cm`, `m` or `in`, which may have a fractional
+/// part, or feet and inches written like `5'10"` or `5ft10in`.
+impl FromStr for Height {
+  type Err = HeightError;
+
+  fn from_str(s: &str) -> Result<Self, HeightError> {
+    for feet_mark in &["'", "ft"] {
+      if let Some((feet, inches)) = s.split_once(feet_mark) {
+        let inches = inches
+          .strip_suffix('"')
+          .or_else(|| inches.strip_suffix("in"))
+          .unwrap_or(inches);
+        let mut micrometers = decimal(feet, HeightUnit::FeetAndInches.micrometers())?;
+        if !inches.is_empty() {
+          micrometers = micrometers
+            .checked_add(decimal(inches, HeightUnit::Inches.micrometers())?)
+            .ok_or(HeightError::Number)?;
+        }
+        return Ok(Self::from_micrometers(
+          micrometers,
+          HeightUnit::FeetAndInches,
+        ));
+      }
+    }
+    // Longer symbols first, so that `cm` isn't read as `m`.
+    let units = [
+      HeightUnit::Millimeters,
+      HeightUnit::Centimeters,
+      HeightUnit::Inches,
+      HeightUnit::Meters,
+    ];
+    for unit in units.iter() {
+      if let Some(number) = s.strip_suffix(unit.symbol()) {
+        return Ok(Self::from_micrometers(
+          decimal(number, unit.micrometers())?,
+          *unit,
+        ));
+      }
+    }
+    Err(HeightError::Unit)
+  }
+}
+impl Display for Height {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.unit {
+      HeightUnit::FeetAndInches => {
+        let foot = HeightUnit::FeetAndInches.micrometers();
+        write!(f, "{}'", self.micrometers / foot)?;
+        let inches = self.micrometers % foot;
+        if inches != 0 {
+          write_decimal(f, inches, HeightUnit::Inches.micrometers())?;
+          write!(f, "\"")?;
+        }
+        Ok(())
+      }
+      unit => {
+        write_decimal(f, self.micrometers, unit.micrometers())?;
+        f.write_str(unit.symbol())
+      }
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HeightError {
+  /// The amount isn't a number, or is too big.
+  Number,
+  /// There's no unit, or it's one we don't know.
+  Unit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum HeightUnit {
+  Millimeters,
+  #[default]
+  Centimeters,
+  Meters,
+  Inches,
+  FeetAndInches,
+}
+impl HeightUnit {
+  pub const ALL: [HeightUnit; 5] = [
+    HeightUnit::Millimeters,
+    HeightUnit::Centimeters,
+    HeightUnit::Meters,
+    HeightUnit::Inches,
+    HeightUnit::FeetAndInches,
+  ];
+
+  /// How the unit is written. Feet and inches are written `ft`.
+  pub fn symbol(&self) -> &'static str {
+    match self {
+      HeightUnit::Millimeters => "mm",
+      HeightUnit::Centimeters => "cm",
+      HeightUnit::Meters => "m",
+      HeightUnit::Inches => "in",
+      HeightUnit::FeetAndInches => "ft",
+    }
+  }
+
+  pub fn from_symbol(symbol: &str) -> Option<Self> {
+    Self::ALL
+      .iter()
+      .copied()
+      .find(|unit| unit.symbol() == symbol)
+  }
+
+  /// The length of one of this unit.
+  pub fn micrometers(&self) -> i64 {
+    match self {
+      HeightUnit::Millimeters => 1_000,
+      HeightUnit::Centimeters => 10_000,
+      HeightUnit::Meters => 1_000_000,
+      HeightUnit::Inches => 25_400,
+      HeightUnit::FeetAndInches => 304_800,
+    }
+  }
+}
+
+/// Reads a non-negative decimal number of units that are each `unit` long,
+/// rounding to the nearest micrometer.
+fn decimal(number: &str, unit: i64) -> Result<i64, HeightError> {
+  let number = number.strip_prefix('+').unwrap_or(number);
+  let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+  let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+  if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) || fraction.len() > 9 {
+    return Err(HeightError::Number);
+  }
+  let scale = 10i64.pow(fraction.len() as u32);
+  let digits: i64 = format!("{}{}", whole, fraction)
+    .parse()
+    .map_err(|_| HeightError::Number)?;
+  let scaled = digits.checked_mul(unit).ok_or(HeightError::Number)?;
+  let rounded = scaled.checked_add(scale / 2).ok_or(HeightError::Number)?;
+  Ok(rounded / scale)
+}
+
+/// Writes `micrometers` as a number of units each `unit` long, with up to six
+/// decimal places.
+fn write_decimal(f: &mut fmt::Formatter, micrometers: i64, unit: i64) -> fmt::Result {
+  write!(f, "{}", micrometers / unit)?;
+  let mut remainder = micrometers % unit;
+  if remainder != 0 {
+    f.write_str(".")?;
+    for _ in 0..6 {
+      if remainder == 0 {
+        break;
+      }
+      remainder *= 10;
+      write!(f, "{}", remainder / unit)?;
+      remainder %= unit;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn units() {
+    let micrometers = |s: &str| s.parse::<Height>().map(|h| h.micrometers());
+    assert_eq!(Ok(1_830_000), micrometers("183cm"));
+    assert_eq!(Ok(1_830_000), micrometers("1830mm"));
+    assert_eq!(Ok(1_830_000), micrometers("1.83m"));
+    assert_eq!(Ok(1_879_600), micrometers("74in"));
+    assert_eq!(Ok(1_778_000), micrometers("5'10\""));
+    assert_eq!(Ok(1_778_000), micrometers("5ft10in"));
+    assert_eq!(Ok(1_524_000), micrometers("5'"));
+    assert_eq!(Ok(1_841_500), micrometers("72.5in"));
+    assert_eq!(Ok(1_700_000), micrometers("+170cm"));
+  }
+
+  #[test]
+  fn bad_heights() {
+    let parse = |s: &str| s.parse::<Height>();
+    assert_eq!(Err(HeightError::Unit), parse(""));
+    assert_eq!(Err(HeightError::Unit), parse("1"));
+    assert_eq!(Err(HeightError::Unit), parse("183"));
+    assert_eq!(Err(HeightError::Unit), parse("183km2"));
+    assert_eq!(Err(HeightError::Number), parse("m"));
+    assert_eq!(Err(HeightError::Number), parse("-2m"));
+    assert_eq!(Err(HeightError::Number), parse("1.2.3m"));
+    assert_eq!(Err(HeightError::Number), parse("99999999999999999m"));
+    assert_eq!(Err(HeightError::Number), parse("922337203685.4775mm"));
+    assert_eq!(None, Height::new(i64::MAX / 2, HeightUnit::Inches));
+    assert_eq!(Err(HeightError::Number), parse("5'ten\""));
+  }
+
+  #[test]
+  fn writes_in_its_unit() {
+    for text in &[
+      "183cm", "1830mm", "1.83m", "72.5in", "5'10\"", "6'", "5'0.5\"",
+    ] {
+      assert_eq!(*text, text.parse::<Height>().unwrap().to_string());
+    }
+    let height: Height = "74in".parse().unwrap();
+    assert_eq!(
+      "187.96cm",
+      height.in_unit(HeightUnit::Centimeters).to_string()
+    );
+    assert_eq!(
+      "6'2\"",
+      height.in_unit(HeightUnit::FeetAndInches).to_string()
+    );
+    assert!(height.same_length(&"187.96cm".parse().unwrap()));
+    assert!((height.centimeters() - 187.96).abs() < 1e-9);
+  }
+}
@@ -0,0 +1,232 @@
+pub use super::height::{Height, HeightUnit};
+use super::schema::{FailureKind, Schema, ValidationFailure};
+use super::PassportCandidate;
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A passport that follows the puzzle's rules, with its values parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Passport {
+  pub birth_year: u16,
+  pub issue_year: u16,
+  pub expiration_year: u16,
+  pub height: Height,
+  pub hair_color: Rgb,
+  pub eye_color: EyeColor,
+  pub passport_id: PassportId,
+  pub country_id: Option<String>,
+}
+/// Fails with everything wrong with the candidate under the puzzle's rules.
+/// Fields the puzzle doesn't describe are dropped.
+impl TryFrom<PassportCandidate<'_>> for Passport {
+  type Error = Vec<ValidationFailure>;
+
+  fn try_from(candidate: PassportCandidate<'_>) -> Result<Self, Self::Error> {
+    let mut failures = candidate.extended_validation(Schema::puzzle());
+    if !failures.is_empty() {
+      return Err(failures);
+    }
+    let passport = Passport {
+      birth_year: typed(&candidate, "byr", &mut failures),
+      issue_year: typed(&candidate, "iyr", &mut failures),
+      expiration_year: typed(&candidate, "eyr", &mut failures),
+      height: typed(&candidate, "hgt", &mut failures),
+      hair_color: typed(&candidate, "hcl", &mut failures),
+      eye_color: typed(&candidate, "ecl", &mut failures),
+      passport_id: typed(&candidate, "pid", &mut failures),
+      country_id: candidate.get("cid").map(str::to_string),
+    };
+    if failures.is_empty() {
+      Ok(passport)
+    } else {
+      Err(failures)
+    }
+  }
+}
+/// The canonical text for the passport, with its fields on one line in the
+/// puzzle's order.
+impl Display for Passport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "byr:{} iyr:{} eyr:{} hgt:{} hcl:{} ecl:{} pid:{}",
+      self.birth_year,
+      self.issue_year,
+      self.expiration_year,
+      self.height,
+      self.hair_color,
+      self.eye_color,
+      self.passport_id
+    )?;
+    if let Some(country_id) = &self.country_id {
+      write!(f, " cid:{}", country_id)?;
+    }
+    Ok(())
+  }
+}
+
+/// Parses the field with `key`, which the schema has already checked. Should
+/// the schema allow something the type doesn't, that's recorded as a failure
+/// and a default stands in for the value.
+fn typed<T: FromStr + Default>(
+  candidate: &PassportCandidate,
+  key: &str,
+  failures: &mut Vec<ValidationFailure>,
+) -> T {
+  let value = candidate.get(key).unwrap_or_default();
+  value.parse().unwrap_or_else(|_| {
+    failures.push(ValidationFailure {
+      field: key.to_string(),
+      value: Some(value.to_string()),
+      kind: FailureKind::BadFormat {
+        expected: format!("a valid {}", key),
+      },
+    });
+    T::default()
+  })
+}
+
+/// A color written as `#rrggbb`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Rgb {
+  pub red: u8,
+  pub green: u8,
+  pub blue: u8,
+}
+impl FromStr for Rgb {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, ()> {
+    let hex = s.strip_prefix('#').ok_or(())?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+      return Err(());
+    }
+    let channel = |idx| u8::from_str_radix(&hex[idx..idx + 2], 16).map_err(|_| ());
+    Ok(Self {
+      red: channel(0)?,
+      green: channel(2)?,
+      blue: channel(4)?,
+    })
+  }
+}
+impl Display for Rgb {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum EyeColor {
+  #[default]
+  Amber,
+  Blue,
+  Brown,
+  Gray,
+  Green,
+  Hazel,
+  Other,
+}
+impl EyeColor {
+  pub fn code(&self) -> &'static str {
+    match self {
+      EyeColor::Amber => "amb",
+      EyeColor::Blue => "blu",
+      EyeColor::Brown => "brn",
+      EyeColor::Gray => "gry",
+      EyeColor::Green => "grn",
+      EyeColor::Hazel => "hzl",
+      EyeColor::Other => "oth",
+    }
+  }
+}
+impl FromStr for EyeColor {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, ()> {
+    Ok(match s {
+      "amb" => EyeColor::Amber,
+      "blu" => EyeColor::Blue,
+      "brn" => EyeColor::Brown,
+      "gry" => EyeColor::Gray,
+      "grn" => EyeColor::Green,
+      "hzl" => EyeColor::Hazel,
+      "oth" => EyeColor::Other,
+      _ => return Err(()),
+    })
+  }
+}
+impl Display for EyeColor {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(self.code())
+  }
+}
+
+/// A nine digit number, which keeps its leading zeros when written out.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct PassportId(pub u32);
+impl FromStr for PassportId {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, ()> {
+    if s.len() != 9 || !s.chars().all(|c| c.is_ascii_digit()) {
+      return Err(());
+    }
+    s.parse().map(PassportId).map_err(|_| ())
+  }
+}
+impl Display for PassportId {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:09}", self.0)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::super::{parse_candidates, EXAMPLE};
+  use super::*;
+
+  #[test]
+  fn typed_fields() {
+    let candidates = parse_candidates(EXAMPLE).unwrap();
+    let passport = Passport::try_from(candidates.into_iter().next().unwrap()).unwrap();
+    assert_eq!(
+      Passport {
+        birth_year: 1937,
+        issue_year: 2017,
+        expiration_year: 2020,
+        height: Height::new(183, HeightUnit::Centimeters).unwrap(),
+        hair_color: Rgb {
+          red: 0xff,
+          green: 0xff,
+          blue: 0xfd
+        },
+        eye_color: EyeColor::Gray,
+        passport_id: PassportId(860033327),
+        country_id: Some("147".to_string()),
+      },
+      passport
+    );
+  }
+
+  #[test]
+  fn canonical_text() {
+    let record = "pid:000000042 hgt:+74in\tecl:grn iyr:2012 eyr:2030\nbyr:1980 hcl:#623A2F zzz:1";
+    let passport = Passport::try_from(PassportCandidate::parse(record).unwrap()).unwrap();
+    let text = passport.to_string();
+    assert_eq!(
+      "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:000000042",
+      text
+    );
+    let reparsed = Passport::try_from(PassportCandidate::parse(&text).unwrap()).unwrap();
+    assert_eq!(passport, reparsed);
+  }
+
+  #[test]
+  fn collects_every_failure() {
+    let record = "byr:2003 iyr:2012 eyr:2030 hgt:190 hcl:623a2f pid:12345678";
+    let failures = Passport::try_from(PassportCandidate::parse(record).unwrap()).unwrap_err();
+    let fields: Vec<&str> = failures.iter().map(|f| f.field.as_str()).collect();
+    assert_eq!(vec!["byr", "hgt", "hcl", "ecl", "pid"], fields);
+  }
+}
@@ -0,0 +1,265 @@
+//! Proposes fixes for passports that fail on a small detail, like a hair
+//! color without its `#`, for a reviewer to accept or reject. Nothing here
+//! changes a record; accepting a fix makes a new one.
+
+use super::exchange::PassportRecord;
+use super::schema::{Constraint, FailureKind, FieldRule, Schema};
+use super::PassportCandidate;
+use std::fmt::{self, Display};
+
+/// A new value for one field, which would make it follow `rule`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Suggestion {
+  pub field: String,
+  pub original: String,
+  pub replacement: String,
+  /// How many chars have to be inserted, deleted or replaced to get from
+  /// `original` to `replacement`.
+  pub distance: usize,
+  /// The schema line the replacement satisfies.
+  pub rule: String,
+}
+impl Suggestion {
+  /// A copy of `record` with the suggestion applied.
+  pub fn apply(&self, record: &PassportRecord) -> PassportRecord {
+    let mut fixed = record.clone();
+    if let Some(value) = fixed.field_mut(&self.field) {
+      *value = Some(self.replacement.clone());
+    } else if let Some((_, value)) = fixed.extras.iter_mut().find(|(key, _)| *key == self.field) {
+      *value = self.replacement.clone();
+    }
+    fixed
+  }
+}
+impl Display for Suggestion {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let edits = if self.distance == 1 { "edit" } else { "edits" };
+    write!(
+      f,
+      "{}: {:?} -> {:?} ({} {}), to satisfy `{}`",
+      self.field, self.original, self.replacement, self.distance, edits, self.rule
+    )
+  }
+}
+
+/// How far a value can be from one of a `one-of` rule's words and still be
+/// taken for a misspelling of it.
+const MAX_MISSPELLING: usize = 2;
+
+/// Fixes for each field of `candidate` whose value breaks `schema`'s rules,
+/// smallest first. Missing fields, and values that are only out of range,
+/// get none, since there's nothing to go on but a guess.
+pub fn suggest(candidate: &PassportCandidate, schema: &Schema) -> Vec<Suggestion> {
+  let mut suggestions = Vec::new();
+  for failure in candidate.extended_validation(schema) {
+    let rule = match schema.field(&failure.field) {
+      Some(rule) => rule,
+      None => continue,
+    };
+    let original = match (&failure.value, &failure.kind) {
+      (_, FailureKind::Missing) | (_, FailureKind::Duplicate { .. }) | (None, _) => continue,
+      (Some(value), _) => value,
+    };
+    let mut fixes: Vec<Suggestion> = Vec::new();
+    for replacement in candidates(original, rule) {
+      let satisfies = rule.check(Some(&replacement)).is_ok();
+      if satisfies && fixes.iter().all(|fix| fix.replacement != replacement) {
+        fixes.push(Suggestion {
+          field: rule.key.clone(),
+          original: original.clone(),
+          distance: edit_distance(original, &replacement),
+          replacement,
+          rule: rule.to_string(),
+        });
+      }
+    }
+    let out_of_range = matches!(failure.kind, FailureKind::OutOfRange { .. });
+    if fixes.is_empty() && !out_of_range {
+      for replacement in single_deletions(original) {
+        let satisfies = rule.check(Some(&replacement)).is_ok();
+        if satisfies && fixes.iter().all(|fix| fix.replacement != replacement) {
+          fixes.push(Suggestion {
+            field: rule.key.clone(),
+            original: original.clone(),
+            distance: 1,
+            replacement,
+            rule: rule.to_string(),
+          });
+        }
+      }
+    }
+    suggestions.extend(fixes);
+  }
+  // A stable sort keeps each field's fixes in the order they were thought of.
+  suggestions.sort_by_key(|suggestion| suggestion.distance);
+  suggestions
+}
+
+/// Likely meanings of `value`, most likely first. They aren't checked
+/// against the rule.
+fn candidates(value: &str, rule: &FieldRule) -> Vec<String> {
+  let mut candidates = vec![value.trim().to_string(), value.to_lowercase()];
+  match &rule.constraint {
+    Constraint::Measure(units) => {
+      candidates.extend(units.iter().map(|(unit, _)| format!("{}{}", value, unit)));
+    }
+    Constraint::Height(unit, _) => candidates.push(format!("{}{}", value, unit.symbol())),
+    Constraint::Pattern(_) => {
+      candidates.push(format!("#{}", value));
+      if let Some(rest) = value.strip_prefix('0') {
+        candidates.push(rest.to_string());
+      }
+    }
+    Constraint::OneOf(words) => {
+      let lowercase = value.to_lowercase();
+      candidates.extend(
+        words
+          .iter()
+          .filter(|word| edit_distance(&lowercase, word) <= MAX_MISSPELLING)
+          .cloned(),
+      );
+    }
+    Constraint::Any | Constraint::Integer(_) => {}
+  }
+  candidates
+}
+
+fn single_deletions(value: &str) -> impl Iterator<Item = String> + '_ {
+  value.char_indices().map(move |(idx, c)| {
+    let mut deleted = value.to_string();
+    deleted.replace_range(idx..idx + c.len_utf8(), "");
+    deleted
+  })
+}
+
+/// The Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  for (i, a_char) in a.chars().enumerate() {
+    let mut current = vec![i + 1; b.len() + 1];
+    for (j, b_char) in b.iter().enumerate() {
+      let substitution = previous[j] + usize::from(a_char != *b_char);
+      current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+    }
+    previous = current;
+  }
+  previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn suggestions(record: &str) -> Vec<String> {
+    let candidate = PassportCandidate::parse(record).unwrap();
+    suggest(&candidate, Schema::puzzle())
+      .iter()
+      .map(|suggestion| suggestion.to_string())
+      .collect()
+  }
+
+  const VALID: &str = "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704";
+
+  #[test]
+  fn common_mistakes() {
+    assert_eq!(
+      vec![
+        "hcl: \"623a2f\" -> \"#623a2f\" (1 edit), to satisfy \
+         `hcl required matches ^#[0-9a-fA-F]{6}$`"
+      ],
+      suggestions(&VALID.replace("#623a2f", "623a2f"))
+    );
+    assert_eq!(
+      vec![
+        "hgt: \"74\" -> \"74in\" (2 edits), to satisfy `hgt required measure cm 150-193 in 59-76`"
+      ],
+      suggestions(&VALID.replace("74in", "74"))
+    );
+    assert_eq!(
+      vec!["pid: \"0087499704\" -> \"087499704\" (1 edit), to satisfy `pid required matches ^[0-9]{9}$`"],
+      suggestions(&VALID.replace("087499704", "0087499704"))
+    );
+    // Other colors are as far from GRN as grn is, but come after it.
+    let ecl = suggestions(&VALID.replace("grn", "GRN"));
+    assert_eq!(
+      "ecl: \"GRN\" -> \"grn\" (3 edits), to satisfy \
+       `ecl required one-of amb blu brn gry grn hzl oth`",
+      ecl[0]
+    );
+    assert_eq!(3, ecl.len());
+  }
+
+  #[test]
+  fn ranked_by_distance() {
+    let record = VALID
+      .replace("#623a2f", "623a2f")
+      .replace("74in", "180")
+      .replace("ecl:grn", "ecl:gren");
+    let ranked: Vec<(String, usize)> = suggest(
+      &PassportCandidate::parse(&record).unwrap(),
+      Schema::puzzle(),
+    )
+    .into_iter()
+    .map(|suggestion| (suggestion.replacement, suggestion.distance))
+    .collect();
+    assert_eq!(
+      vec![
+        ("#623a2f".to_string(), 1),
+        ("grn".to_string(), 1),
+        ("180cm".to_string(), 2),
+        ("brn".to_string(), 2),
+        ("gry".to_string(), 2),
+      ],
+      ranked
+    );
+  }
+
+  #[test]
+  fn no_repeated_suggestions() {
+    let pid = suggestions(&VALID.replace("087499704", "1234567899"));
+    assert_eq!(
+      vec![
+        "pid: \"1234567899\" -> \"234567899\" (1 edit), to satisfy \
+         `pid required matches ^[0-9]{9}$`",
+        "pid: \"1234567899\" -> \"134567899\" (1 edit), to satisfy \
+         `pid required matches ^[0-9]{9}$`",
+      ],
+      pid[..2].to_vec()
+    );
+    let mut replacements: Vec<&str> = pid.iter().map(|s| s.as_str()).collect();
+    replacements.sort_unstable();
+    replacements.dedup();
+    assert_eq!(pid.len(), replacements.len());
+    assert_eq!(9, pid.len());
+  }
+
+  #[test]
+  fn nothing_to_go_on() {
+    assert!(suggestions(&VALID.replace("byr:1980", "byr:1880")).is_empty());
+    assert!(suggestions(&VALID.replace("byr:1980", "byr:19800")).is_empty());
+    assert!(suggestions(&VALID.replace(" pid:087499704", "")).is_empty());
+    assert!(suggestions(VALID).is_empty());
+  }
+
+  #[test]
+  fn never_changes_the_original() {
+    let text = VALID.replace("#623a2f", "623a2f");
+    let candidate = PassportCandidate::parse(&text).unwrap();
+    let record = PassportRecord::from_candidate(&candidate);
+    let suggestion = suggest(&candidate, Schema::puzzle()).remove(0);
+    let fixed = suggestion.apply(&record);
+    assert_eq!(Some("623a2f"), record.hcl.as_deref());
+    assert_eq!(Some("623a2f"), candidate.get("hcl"));
+    assert!(!candidate.validate(Schema::puzzle()));
+    assert!(fixed.candidate().validate(Schema::puzzle()));
+  }
+
+  #[test]
+  fn edit_distances() {
+    assert_eq!(0, edit_distance("", ""));
+    assert_eq!(3, edit_distance("kitten", "sitting"));
+    assert_eq!(1, edit_distance("grn", "gren"));
+    assert_eq!(1, edit_distance("é", "e"));
+  }
+}
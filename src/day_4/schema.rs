@@ -0,0 +1,619 @@
+//! A small language for passport rules, so that they can be changed without
+//! rebuilding.
+//!
+//! A schema has one line per field, giving its key, whether it's `required`
+//! or `optional`, and then at most one constraint on its value:
+//!
+//! - `int N-M`: a whole number from N to M.
+//! - `measure UNIT N-M ...`: a whole number followed by one of the units,
+//!   within that unit's range, like `measure cm 150-193 in 59-76`.
+//! - `height UNIT N-M`: a height in any unit that `Height` reads, which is
+//!   from N to M of UNIT, like `height cm 150-193`.
+//! - `matches REGEX`: the whole value matches REGEX, which can't contain
+//!   spaces.
+//! - `one-of WORD ...`: exactly one of the words.
+//!
+//! Optional fields with a constraint only need to meet it when present.
+//! Blank lines and lines starting with `#` are ignored.
+
+use super::height::{Height, HeightError, HeightUnit};
+use crate::parse::{self, ParseError};
+use crate::{Args, FromArgs};
+use regex::Regex;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// The rules from part 2 of the puzzle.
+pub const PUZZLE: &str = "\
+byr required int 1920-2002
+iyr required int 2010-2020
+eyr required int 2020-2030
+hgt required measure cm 150-193 in 59-76
+hcl required matches ^#[0-9a-fA-F]{6}$
+ecl required one-of amb blu brn gry grn hzl oth
+pid required matches ^[0-9]{9}$
+cid optional
+";
+
+#[derive(Clone, Debug)]
+pub struct Schema {
+  pub fields: Vec<FieldRule>,
+}
+impl Schema {
+  pub fn parse(text: &str) -> Result<Self, ParseError> {
+    let mut fields: Vec<FieldRule> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+      if line.trim().is_empty() || line.trim_start().starts_with('#') {
+        continue;
+      }
+      let rule = FieldRule::parse(line).map_err(|e| e.offset(idx, 0))?;
+      if fields.iter().any(|other| other.key == rule.key) {
+        return Err(ParseError::new(
+          idx + 1,
+          1,
+          format!("{} is already described", rule.key),
+        ));
+      }
+      fields.push(rule);
+    }
+    Ok(Self { fields })
+  }
+
+  /// The puzzle's rules, parsed once.
+  pub fn puzzle() -> &'static Schema {
+    lazy_static! {
+      static ref SCHEMA: Schema = Schema::parse(PUZZLE).unwrap();
+    }
+    &SCHEMA
+  }
+
+  pub fn field(&self, key: &str) -> Option<&FieldRule> {
+    self.fields.iter().find(|rule| rule.key == key)
+  }
+
+  pub fn required(&self) -> impl Iterator<Item = &FieldRule> {
+    self.fields.iter().filter(|rule| rule.required)
+  }
+}
+impl FromStr for Schema {
+  type Err = ParseError;
+
+  fn from_str(text: &str) -> Result<Self, ParseError> {
+    Self::parse(text)
+  }
+}
+/// Reads the schema from the file named by the `schema` argument, or uses the
+/// puzzle's rules.
+impl FromArgs for Schema {
+  const NAMES: &'static [&'static str] = &["schema"];
+
+  fn from_args(args: &Args) -> Result<Self, String> {
+    match args.get::<String>("schema")? {
+      None => Ok(Self::puzzle().clone()),
+      Some(path) => {
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+      }
+    }
+  }
+}
+
+/// What a schema says about one field.
+#[derive(Clone, Debug)]
+pub struct FieldRule {
+  pub key: String,
+  pub required: bool,
+  pub constraint: Constraint,
+}
+impl FieldRule {
+  fn parse(line: &str) -> Result<Self, ParseError> {
+    let words = parse::words(line);
+    let mut words = words.into_iter();
+    let (_, key) = words.next().unwrap();
+    let required = match words.next() {
+      Some((_, "required")) => true,
+      Some((_, "optional")) => false,
+      Some((column, word)) => {
+        return Err(ParseError::at(
+          column,
+          format!("expected `required` or `optional`, got {:?}", word),
+        ))
+      }
+      None => {
+        return Err(ParseError::at(
+          line.chars().count() + 1,
+          "expected `required` or `optional`",
+        ))
+      }
+    };
+    let constraint = Constraint::parse(words.collect(), line.chars().count() + 1)?;
+    Ok(Self {
+      key: key.to_string(),
+      required,
+      constraint,
+    })
+  }
+
+  /// Whether `value`, the field's value in a passport if it has one, follows
+  /// this rule.
+  pub fn allows(&self, value: Option<&str>) -> bool {
+    self.check(value).is_ok()
+  }
+
+  /// Like `allows`, but says how the value breaks the rule.
+  pub fn check(&self, value: Option<&str>) -> Result<(), ValidationFailure> {
+    let kind = match value {
+      None if self.required => FailureKind::Missing,
+      None => return Ok(()),
+      Some(value) => match self.constraint.check(value) {
+        Ok(()) => return Ok(()),
+        Err(kind) => kind,
+      },
+    };
+    Err(ValidationFailure {
+      field: self.key.clone(),
+      value: value.map(str::to_string),
+      kind,
+    })
+  }
+}
+
+/// The rule as a line of a schema.
+impl Display for FieldRule {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let status = if self.required {
+      "required"
+    } else {
+      "optional"
+    };
+    write!(f, "{} {}", self.key, status)?;
+    match &self.constraint {
+      Constraint::Any => Ok(()),
+      Constraint::Integer(bounds) => write!(f, " int {}", bounds),
+      Constraint::Measure(units) => {
+        f.write_str(" measure")?;
+        for (unit, bounds) in units {
+          write!(f, " {} {}", unit, bounds)?;
+        }
+        Ok(())
+      }
+      Constraint::Height(unit, bounds) => write!(f, " height {} {}", unit.symbol(), bounds),
+      Constraint::Pattern(regex) => write!(f, " matches {}", regex),
+      Constraint::OneOf(words) => write!(f, " one-of {}", words.join(" ")),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum Constraint {
+  Any,
+  Integer(Bounds),
+  /// Ranges for each unit the value can be measured in.
+  Measure(Vec<(String, Bounds)>),
+  /// Bounds on a height, in the given unit.
+  Height(HeightUnit, Bounds),
+  Pattern(Regex),
+  OneOf(Vec<String>),
+}
+impl Constraint {
+  /// Parses the words after a field's status. `end` is the column just past
+  /// the line, for errors about words that are missing.
+  fn parse(words: Vec<(usize, &str)>, end: usize) -> Result<Self, ParseError> {
+    let (column, keyword) = match words.first() {
+      None => return Ok(Constraint::Any),
+      Some(word) => *word,
+    };
+    let args = &words[1..];
+    let next_column = args.first().map_or(end, |(column, _)| *column);
+    let constraint = match keyword {
+      "int" => match args {
+        [(column, range)] => Constraint::Integer(Bounds::parse(range, *column)?),
+        _ => return Err(ParseError::at(next_column, "expected a range, like `1-10`")),
+      },
+      "measure" => {
+        if args.is_empty() || !args.len().is_multiple_of(2) {
+          return Err(ParseError::at(
+            next_column,
+            "expected units and their ranges, like `cm 150-193`",
+          ));
+        }
+        let mut units = Vec::new();
+        for pair in args.chunks(2) {
+          let (column, range) = pair[1];
+          units.push((pair[0].1.to_string(), Bounds::parse(range, column)?));
+        }
+        Constraint::Measure(units)
+      }
+      "height" => match args {
+        [(column, unit), (range_column, range)] => {
+          let unit = HeightUnit::from_symbol(unit).ok_or_else(|| {
+            ParseError::at(
+              *column,
+              format!("expected mm, cm, m, in or ft, got {:?}", unit),
+            )
+          })?;
+          let bounds = Bounds::parse(range, *range_column)?;
+          let too_long = |bound: i64| bound.checked_mul(unit.micrometers()).is_none();
+          if too_long(bounds.min) || too_long(bounds.max) {
+            return Err(ParseError::at(
+              *range_column,
+              format!("{} is too long a range of {}", range, unit.symbol()),
+            ));
+          }
+          Constraint::Height(unit, bounds)
+        }
+        _ => {
+          return Err(ParseError::at(
+            next_column,
+            "expected a unit and a range, like `cm 150-193`",
+          ))
+        }
+      },
+      "matches" => match args {
+        [(column, pattern)] => Constraint::Pattern(
+          Regex::new(pattern).map_err(|e| ParseError::at(*column, e.to_string()))?,
+        ),
+        _ => {
+          return Err(ParseError::at(
+            next_column,
+            "expected one regex, without spaces",
+          ))
+        }
+      },
+      "one-of" => {
+        if args.is_empty() {
+          return Err(ParseError::at(next_column, "expected at least one word"));
+        }
+        Constraint::OneOf(args.iter().map(|(_, word)| word.to_string()).collect())
+      }
+      _ => {
+        return Err(ParseError::at(
+          column,
+          format!(
+            "expected `int`, `measure`, `height`, `matches` or `one-of`, got {:?}",
+            keyword
+          ),
+        ))
+      }
+    };
+    Ok(constraint)
+  }
+
+  pub fn allows(&self, value: &str) -> bool {
+    self.check(value).is_ok()
+  }
+
+  pub fn check(&self, value: &str) -> Result<(), FailureKind> {
+    match self {
+      Constraint::Any => Ok(()),
+      Constraint::Integer(bounds) => {
+        let number = value.parse().map_err(|_| FailureKind::Unparseable)?;
+        bounds.check(number, None)
+      }
+      Constraint::Measure(units) => {
+        let mut failure = None;
+        for (unit, bounds) in units {
+          let number = match value.strip_suffix(unit.as_str()) {
+            None => continue,
+            Some(number) => number,
+          };
+          let result = match number.parse() {
+            Ok(number) => bounds.check(number, Some(unit)),
+            Err(_) => Err(FailureKind::Unparseable),
+          };
+          if let Err(kind) = result {
+            // A number out of range says more than one that doesn't parse.
+            let replaces = matches!(
+              (&failure, &kind),
+              (None, _)
+                | (
+                  Some(FailureKind::Unparseable),
+                  FailureKind::OutOfRange { .. }
+                )
+            );
+            if replaces {
+              failure = Some(kind);
+            }
+          } else {
+            return Ok(());
+          }
+        }
+        Err(failure.unwrap_or_else(|| FailureKind::WrongUnit {
+          expected: units.iter().map(|(unit, _)| unit.clone()).collect(),
+        }))
+      }
+      Constraint::Height(unit, bounds) => {
+        let height: Height = value.parse().map_err(|e| match e {
+          HeightError::Number => FailureKind::Unparseable,
+          HeightError::Unit => FailureKind::WrongUnit {
+            expected: HeightUnit::ALL
+              .iter()
+              .map(|unit| unit.symbol().to_string())
+              .collect(),
+          },
+        })?;
+        let length = height.micrometers();
+        // Schemas can't give bounds this large, but if one did, nothing would
+        // be in range.
+        let min = bounds.min.checked_mul(unit.micrometers());
+        let max = bounds.max.checked_mul(unit.micrometers());
+        let within = matches!((min, max), (Some(min), Some(max)) if min <= length && length <= max);
+        if within {
+          Ok(())
+        } else {
+          Err(FailureKind::OutOfRange {
+            bounds: *bounds,
+            unit: Some(unit.symbol().to_string()),
+          })
+        }
+      }
+      Constraint::Pattern(regex) if regex.is_match(value) => Ok(()),
+      Constraint::Pattern(regex) => Err(FailureKind::BadFormat {
+        expected: format!("a match for {}", regex),
+      }),
+      Constraint::OneOf(words) if words.iter().any(|word| word == value) => Ok(()),
+      Constraint::OneOf(words) => Err(FailureKind::BadFormat {
+        expected: format!("one of {}", words.join(", ")),
+      }),
+    }
+  }
+}
+
+/// An inclusive range of whole numbers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Bounds {
+  pub min: i64,
+  pub max: i64,
+}
+impl Bounds {
+  fn parse(range: &str, column: usize) -> Result<Self, ParseError> {
+    let (min, max) = range
+      .split_once('-')
+      .ok_or_else(|| ParseError::at(column, format!("expected a range, got {:?}", range)))?;
+    Ok(Self {
+      min: parse::number(min).map_err(|e| e.offset(0, column - 1))?,
+      max: parse::number(max).map_err(|e| e.offset(0, column + min.len()))?,
+    })
+  }
+
+  pub fn contains(&self, value: i64) -> bool {
+    self.min <= value && value <= self.max
+  }
+
+  fn check(&self, value: i64, unit: Option<&str>) -> Result<(), FailureKind> {
+    if self.contains(value) {
+      Ok(())
+    } else {
+      Err(FailureKind::OutOfRange {
+        bounds: *self,
+        unit: unit.map(str::to_string),
+      })
+    }
+  }
+}
+impl Display for Bounds {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}-{}", self.min, self.max)
+  }
+}
+
+/// A field of a passport that breaks its rule, and its value, if it has one.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ValidationFailure {
+  pub field: String,
+  pub value: Option<String>,
+  pub kind: FailureKind,
+}
+impl Display for ValidationFailure {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let value = self.value.as_deref().unwrap_or_default();
+    match &self.kind {
+      FailureKind::Missing => write!(f, "{} is missing", self.field),
+      FailureKind::Unparseable => write!(f, "{}: {:?} isn't a number", self.field, value),
+      FailureKind::OutOfRange { bounds, unit } => write!(
+        f,
+        "{}: {:?} is outside {}{}",
+        self.field,
+        value,
+        bounds,
+        unit.as_deref().unwrap_or_default()
+      ),
+      FailureKind::WrongUnit { expected } => write!(
+        f,
+        "{}: {:?} isn't in {}",
+        self.field,
+        value,
+        expected.join(" or ")
+      ),
+      FailureKind::BadFormat { expected } => {
+        write!(f, "{}: {:?} isn't {}", self.field, value, expected)
+      }
+      FailureKind::Duplicate { first } => write!(
+        f,
+        "{}: {:?} repeats a field already set to {:?}",
+        self.field, value, first
+      ),
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FailureKind {
+  Missing,
+  /// A number was expected.
+  Unparseable,
+  /// The number isn't within `bounds`, in `unit` if the rule has units.
+  OutOfRange {
+    bounds: Bounds,
+    unit: Option<String>,
+  },
+  /// The value doesn't end with any of the `expected` units.
+  WrongUnit {
+    expected: Vec<String>,
+  },
+  BadFormat {
+    expected: String,
+  },
+  /// The field appeared more than once. Its `first` value is the one that
+  /// was checked against the rules.
+  Duplicate {
+    first: String,
+  },
+}
+impl FailureKind {
+  /// A short name for the kind of failure, without its details.
+  pub fn name(&self) -> &'static str {
+    match self {
+      FailureKind::Missing => "missing",
+      FailureKind::Unparseable => "unparseable",
+      FailureKind::OutOfRange { .. } => "out of range",
+      FailureKind::WrongUnit { .. } => "wrong unit",
+      FailureKind::BadFormat { .. } => "bad format",
+      FailureKind::Duplicate { .. } => "duplicate",
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn puzzle_rules() {
+    let schema = Schema::puzzle();
+    assert_eq!(7, schema.required().count());
+    let allows = |key, value| schema.field(key).unwrap().allows(value);
+    assert!(allows("byr", Some("2002")));
+    assert!(!allows("byr", Some("2003")));
+    assert!(allows("hgt", Some("60in")));
+    assert!(allows("hgt", Some("190cm")));
+    assert!(!allows("hgt", Some("190in")));
+    assert!(!allows("hgt", Some("190")));
+    assert!(!allows("hgt", Some("")));
+    assert!(allows("hcl", Some("#123abc")));
+    assert!(!allows("hcl", Some("#123abz")));
+    assert!(!allows("hcl", Some("123abc")));
+    assert!(allows("ecl", Some("brn")));
+    assert!(!allows("ecl", Some("wat")));
+    assert!(allows("pid", Some("000000001")));
+    assert!(!allows("pid", Some("0123456789")));
+    assert!(!allows("pid", None));
+    assert!(allows("cid", None));
+    assert!(allows("cid", Some("anything")));
+  }
+
+  #[test]
+  fn failures() {
+    let schema = Schema::puzzle();
+    let check = |key, value| {
+      schema
+        .field(key)
+        .unwrap()
+        .check(value)
+        .map_err(|failure| failure.to_string())
+    };
+    assert_eq!(Ok(()), check("hgt", Some("150cm")));
+    assert_eq!(Err("byr is missing".to_string()), check("byr", None));
+    assert_eq!(
+      Err("byr: \"19x0\" isn't a number".to_string()),
+      check("byr", Some("19x0"))
+    );
+    assert_eq!(
+      Err("hgt: \"190in\" is outside 59-76in".to_string()),
+      check("hgt", Some("190in"))
+    );
+    assert_eq!(
+      Err("hgt: \"tallin\" isn't a number".to_string()),
+      check("hgt", Some("tallin"))
+    );
+    assert_eq!(
+      Err("hgt: \"190\" isn't in cm or in".to_string()),
+      check("hgt", Some("190"))
+    );
+    assert_eq!(
+      Err("ecl: \"wat\" isn't one of amb, blu, brn, gry, grn, hzl, oth".to_string()),
+      check("ecl", Some("wat"))
+    );
+    let failure = schema
+      .field("byr")
+      .unwrap()
+      .check(Some("2003"))
+      .unwrap_err();
+    assert_eq!(
+      ValidationFailure {
+        field: "byr".to_string(),
+        value: Some("2003".to_string()),
+        kind: FailureKind::OutOfRange {
+          bounds: Bounds {
+            min: 1920,
+            max: 2002
+          },
+          unit: None
+        },
+      },
+      failure
+    );
+  }
+
+  #[test]
+  fn heights_in_any_unit() {
+    let schema = Schema::parse("hgt required height cm 150-193").unwrap();
+    let check = |value| schema.fields[0].constraint.check(value);
+    for value in &["150cm", "1930mm", "1.7m", "74in", "5'10\"", "6ft"] {
+      assert_eq!(Ok(()), check(value), "{}", value);
+    }
+    assert!(matches!(check("76in"), Err(FailureKind::OutOfRange { .. })));
+    assert!(matches!(
+      check("4'11\""),
+      Err(FailureKind::OutOfRange { .. })
+    ));
+    assert!(matches!(check("183"), Err(FailureKind::WrongUnit { .. })));
+    assert!(matches!(check("tallcm"), Err(FailureKind::Unparseable)));
+    assert!(matches!(check("1"), Err(FailureKind::WrongUnit { .. })));
+    let err = Schema::parse("hgt required height yd 1-2").unwrap_err();
+    assert_eq!((1, 21), (err.line, err.column));
+    let err = Schema::parse("hgt required height ft 1-99999999999999").unwrap_err();
+    assert_eq!((1, 24), (err.line, err.column));
+    assert!(matches!(
+      check("922337203685.4775mm"),
+      Err(FailureKind::Unparseable)
+    ));
+  }
+
+  #[test]
+  fn writes_rules_back() {
+    let lines: Vec<String> = Schema::puzzle()
+      .fields
+      .iter()
+      .map(|rule| rule.to_string())
+      .collect();
+    assert_eq!(PUZZLE.lines().collect::<Vec<_>>(), lines);
+    let rule = Schema::parse("hgt optional height ft 4-7").unwrap();
+    assert_eq!("hgt optional height ft 4-7", rule.fields[0].to_string());
+  }
+
+  #[test]
+  fn comments_and_optional_constraints() {
+    let schema =
+      Schema::parse("# Country is optional, but numeric.\n\ncid optional int 1-999\n").unwrap();
+    let cid = schema.field("cid").unwrap();
+    assert!(cid.allows(None));
+    assert!(cid.allows(Some("350")));
+    assert!(!cid.allows(Some("abc")));
+  }
+
+  #[test]
+  fn malformed_schemas() {
+    let err = |text| {
+      let err = Schema::parse(text).unwrap_err();
+      (err.line, err.column)
+    };
+    assert_eq!((1, 5), err("byr needed"));
+    assert_eq!((2, 18), err("cid optional\nbyr required int 1920"));
+    assert_eq!((1, 23), err("byr required int 1920-20o2"));
+    assert_eq!((1, 14), err("hgt required cm 150-193"));
+    assert_eq!((1, 25), err("hgt required measure cm 150"));
+    assert_eq!((1, 22), err("hcl required matches #[0-9"));
+    assert_eq!((2, 1), err("cid optional\ncid required"));
+  }
+}
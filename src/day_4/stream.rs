@@ -0,0 +1,268 @@
+//! Validates passports as they're read, for batches too big to hold in memory
+//! at once, spreading the work over a pool of threads.
+
+use super::exchange::PassportRecord;
+use super::schema::{Schema, ValidationFailure};
+use super::PassportCandidate;
+use crate::parse::{ParseError, ReadError};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::io::{BufRead, ErrorKind};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Reads records separated by blank lines, one at a time.
+pub struct RecordReader<R> {
+  reader: R,
+  /// Lines read so far.
+  line: usize,
+  done: bool,
+}
+impl<R: BufRead> RecordReader<R> {
+  pub fn new(reader: R) -> Self {
+    Self {
+      reader,
+      line: 0,
+      done: false,
+    }
+  }
+
+  /// The next record's text, and the number of lines before it. A line
+  /// that isn't UTF-8 is a parse error on that line.
+  fn next_text(&mut self) -> Result<Option<(usize, String)>, ReadError> {
+    let mut text = String::new();
+    let mut start = None;
+    let mut line = String::new();
+    loop {
+      line.clear();
+      let line_number = self.line + 1;
+      let read = self
+        .reader
+        .read_line(&mut line)
+        .map_err(|e| match e.kind() {
+          ErrorKind::InvalidData => ParseError::new(line_number, 1, "line isn't UTF-8").into(),
+          _ => ReadError::from(e),
+        })?;
+      if read == 0 {
+        return Ok(start.map(|start| (start, text)));
+      }
+      self.line += 1;
+      if line.trim().is_empty() {
+        if let Some(start) = start {
+          return Ok(Some((start, text)));
+        }
+      } else {
+        start.get_or_insert(self.line - 1);
+        text.push_str(&line);
+      }
+    }
+  }
+}
+/// A record that doesn't parse gives an error, and reading goes on with the
+/// next one. Reading stops after an I/O error, or a line that isn't UTF-8.
+impl<R: BufRead> Iterator for RecordReader<R> {
+  type Item = Result<PassportRecord, ReadError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.done {
+      return None;
+    }
+    let (start, text) = match self.next_text() {
+      Ok(Some(record)) => record,
+      Ok(None) => {
+        self.done = true;
+        return None;
+      }
+      Err(e) => {
+        self.done = true;
+        return Some(Err(e));
+      }
+    };
+    Some(
+      PassportCandidate::parse(&text)
+        .map(|candidate| PassportRecord::from_candidate(&candidate))
+        .map_err(|e| ReadError::Parse(e.offset(start, 0))),
+    )
+  }
+}
+
+/// Running counts of the passports checked so far.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Tally {
+  pub valid: usize,
+  pub invalid: usize,
+  /// How many passports broke a rule, by field and `FailureKind::name`.
+  pub reasons: BTreeMap<(String, &'static str), usize>,
+}
+impl Tally {
+  /// Counts a passport with `failures`, which is valid when there are none.
+  pub fn add(&mut self, failures: &[ValidationFailure]) {
+    if failures.is_empty() {
+      self.valid += 1;
+    } else {
+      self.invalid += 1;
+    }
+    for failure in failures {
+      *self
+        .reasons
+        .entry((failure.field.clone(), failure.kind.name()))
+        .or_insert(0) += 1;
+    }
+  }
+
+  pub fn total(&self) -> usize {
+    self.valid + self.invalid
+  }
+}
+impl Display for Tally {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} valid, {} invalid", self.valid, self.invalid)?;
+    for ((field, reason), count) in &self.reasons {
+      write!(f, "; {} {}: {}", field, reason, count)?;
+    }
+    Ok(())
+  }
+}
+
+/// Checks every record read from `reader` against `schema` on `workers`
+/// threads, calling `progress` with the counts so far after each one. Stops
+/// at the first record that can't be read.
+pub fn validate_stream(
+  reader: impl BufRead + Send,
+  schema: &Schema,
+  workers: usize,
+  mut progress: impl FnMut(&Tally),
+) -> Result<Tally, ReadError> {
+  let (record_sender, records) = mpsc::sync_channel::<PassportRecord>(workers.max(1) * 16);
+  let records = Arc::new(Mutex::new(records));
+  let (result_sender, results) = mpsc::channel();
+  thread::scope(|scope| {
+    let reader_results = result_sender.clone();
+    scope.spawn(move || {
+      for record in RecordReader::new(reader) {
+        let sent = match record {
+          Ok(record) => record_sender.send(record).is_ok(),
+          Err(e) => {
+            let _ = reader_results.send(Err(e));
+            false
+          }
+        };
+        if !sent {
+          break;
+        }
+      }
+    });
+    for _ in 0..workers.max(1) {
+      let records = Arc::clone(&records);
+      let results = result_sender.clone();
+      scope.spawn(move || loop {
+        // The lock is released before the record is checked.
+        let record = records.lock().unwrap().recv();
+        match record {
+          Ok(record) => {
+            let failures = record.candidate().extended_validation(schema);
+            if results.send(Ok(failures)).is_err() {
+              break;
+            }
+          }
+          Err(_) => break,
+        }
+      });
+    }
+    drop(result_sender);
+
+    let mut tally = Tally::default();
+    let mut error = None;
+    for result in results {
+      match result {
+        Ok(failures) => {
+          tally.add(&failures);
+          progress(&tally);
+        }
+        Err(e) => {
+          error.get_or_insert(e);
+        }
+      }
+    }
+    match error {
+      Some(e) => Err(e),
+      None => Ok(tally),
+    }
+  })
+}
+
+/// One worker per core.
+pub fn default_workers() -> usize {
+  thread::available_parallelism().map_or(1, usize::from)
+}
+
+#[cfg(test)]
+mod test {
+  use super::super::{parse_candidates, EXAMPLE};
+  use super::*;
+
+  #[test]
+  fn reads_records_one_at_a_time() {
+    let input = "\r\nbyr:1937\r\niyr:2017\r\n\r\n\r\n\tcid:1 \r\n";
+    let records: Vec<_> = RecordReader::new(input.as_bytes())
+      .map(|record| record.unwrap().to_string())
+      .collect();
+    assert_eq!(vec!["byr:1937 iyr:2017", "cid:1"], records);
+
+    let mut reader = RecordReader::new("byr:1937\n\nbyr:1937 iyr\n\ncid:1\n".as_bytes());
+    assert!(reader.next().unwrap().is_ok());
+    match reader.next().unwrap() {
+      Err(ReadError::Parse(e)) => assert_eq!((3, 10), (e.line, e.column)),
+      other => panic!("{:?}", other),
+    }
+    assert_eq!("cid:1", reader.next().unwrap().unwrap().to_string());
+    assert!(reader.next().is_none());
+
+    let mut reader = RecordReader::new(&b"byr:1937\n\nbyr:\xff\ncid:1\n"[..]);
+    assert!(reader.next().unwrap().is_ok());
+    match reader.next().unwrap() {
+      Err(ReadError::Parse(e)) => assert_eq!((3, 1), (e.line, e.column)),
+      other => panic!("{:?}", other),
+    }
+    assert!(reader.next().is_none());
+  }
+
+  #[test]
+  fn tallies_reasons() {
+    let mut reports = Vec::new();
+    let tally = validate_stream(EXAMPLE.as_bytes(), Schema::puzzle(), 3, |tally| {
+      reports.push(tally.total())
+    })
+    .unwrap();
+    assert_eq!(vec![1, 2, 3, 4], reports);
+    assert_eq!((2, 2), (tally.valid, tally.invalid));
+    assert_eq!(
+      "2 valid, 2 invalid; byr missing: 1; hgt missing: 1",
+      tally.to_string()
+    );
+  }
+
+  #[test]
+  fn matches_validating_in_memory() {
+    let input = my_input!(4);
+    let mut expected = Tally::default();
+    for candidate in parse_candidates(&input).unwrap() {
+      expected.add(&candidate.extended_validation(Schema::puzzle()));
+    }
+    assert_eq!(145, expected.valid);
+    for workers in &[1, 4] {
+      let tally = validate_stream(input.as_bytes(), Schema::puzzle(), *workers, |_| {}).unwrap();
+      assert_eq!(expected, tally);
+    }
+  }
+
+  #[test]
+  fn stops_at_unreadable_records() {
+    let input = format!("{}\nbyr:1937 iyr\n", EXAMPLE);
+    match validate_stream(input.as_bytes(), Schema::puzzle(), 2, |_| {}) {
+      Err(ReadError::Parse(e)) => assert_eq!(15, e.line),
+      other => panic!("{:?}", other),
+    }
+  }
+}
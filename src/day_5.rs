@@ -1,30 +1,48 @@
+use crate::parse::{self, ParseError};
+use crate::{Example, Solution};
+
 struct Index {
   plane_partitionings: [PlanePartitioning; 7],
   row_partitionings: [RowPartitioning; 3],
 }
 impl Index {
-  fn parse(input: &str) -> Self {
+  fn parse(input: &str) -> Result<Self, ParseError> {
     let mut plane_partitionings = [PlanePartitioning::Back; 7];
-    let mut chars = input.chars();
+    let mut chars = input.chars().enumerate();
     for part in plane_partitionings.iter_mut() {
       *part = match chars.next() {
-        Some('F') => PlanePartitioning::Front,
-        Some('B') => PlanePartitioning::Back,
-        c => panic!("Couldn't get next plane partition? Got: {:?}", c),
+        Some((_, 'F')) => PlanePartitioning::Front,
+        Some((_, 'B')) => PlanePartitioning::Back,
+        Some((idx, c)) => {
+          return Err(ParseError::at(
+            idx + 1,
+            format!("expected F or B, got {:?}", c),
+          ));
+        }
+        None => return Err(ParseError::at(1, "boarding pass is too short")),
       }
     }
     let mut row_partitionings = [RowPartitioning::Left; 3];
     for part in row_partitionings.iter_mut() {
       *part = match chars.next() {
-        Some('L') => RowPartitioning::Left,
-        Some('R') => RowPartitioning::Right,
-        c => panic!("Couldn't get next row partition? Got: {:?}", c),
+        Some((_, 'L')) => RowPartitioning::Left,
+        Some((_, 'R')) => RowPartitioning::Right,
+        Some((idx, c)) => {
+          return Err(ParseError::at(
+            idx + 1,
+            format!("expected L or R, got {:?}", c),
+          ));
+        }
+        None => return Err(ParseError::at(1, "boarding pass is too short")),
       }
     }
-    Self {
+    if let Some((idx, _)) = chars.find(|(_, c)| !c.is_whitespace()) {
+      return Err(ParseError::at(idx + 1, "boarding pass is too long"));
+    }
+    Ok(Self {
       plane_partitionings,
       row_partitionings,
-    }
+    })
   }
 }
 
@@ -143,24 +161,52 @@ impl Row {
   }
 }
 
-pub fn problem(input: &str) -> usize {
+pub fn problem(input: &str) -> Result<Option<usize>, ParseError> {
   let mut plane = Plane::new();
-  input
-    .trim()
-    .lines()
-    .map(|line| plane.seat_id(&Index::parse(line)))
-    .max()
-    .unwrap()
+  let tickets = parse::lines(input, Index::parse)?;
+  Ok(tickets.iter().map(|ticket| plane.seat_id(ticket)).max())
 }
 
-pub fn problem_part_2(input: &str) -> usize {
+pub fn problem_part_2(input: &str) -> Result<Option<usize>, ParseError> {
   let mut plane = Plane::new();
-  let tickets = input.trim().lines().map(|line| Index::parse(line));
+  let tickets = parse::lines(input, Index::parse)?;
   for ticket in tickets {
     let (_, seat) = plane.get(&ticket);
     *seat = true;
   }
-  plane.find_my_seat().unwrap()
+  Ok(plane.find_my_seat())
+}
+
+pub const EXAMPLE: &str = "\
+FBFBBFFRLR
+BFFFBBFRRR
+FFFBBBFRRR
+BBFFBBFRLL
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = Option<usize>;
+  const DAY: u8 = 5;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<usize>, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = Option<usize>;
+  const DAY: u8 = 5;
+  const PART: u8 = 2;
+
+  fn solve(text: &str, _input: ()) -> Result<Option<usize>, ParseError> {
+    problem_part_2(text)
+  }
 }
 
 #[cfg(test)]
@@ -170,32 +216,30 @@ mod test {
   #[test]
   fn examples() {
     let mut plane = Plane::new();
-    assert_eq!(357, plane.seat_id(&Index::parse("FBFBBFFRLR")));
-    assert_eq!(567, plane.seat_id(&Index::parse("BFFFBBFRRR")));
-    assert_eq!(119, plane.seat_id(&Index::parse("FFFBBBFRRR")));
-    assert_eq!(820, plane.seat_id(&Index::parse("BBFFBBFRLL")));
-    assert_eq!(
-      820,
-      problem(
-        "
-FBFBBFFRLR
-BFFFBBFRRR
-FFFBBBFRRR
-BBFFBBFRLL
-"
-      )
-    );
+    assert_eq!(357, plane.seat_id(&Index::parse("FBFBBFFRLR").unwrap()));
+    assert_eq!(567, plane.seat_id(&Index::parse("BFFFBBFRRR").unwrap()));
+    assert_eq!(119, plane.seat_id(&Index::parse("FFFBBBFRRR").unwrap()));
+    assert_eq!(820, plane.seat_id(&Index::parse("BBFFBBFRLL").unwrap()));
+    assert_eq!(Ok(Some(820)), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(930, problem(MY_INPUT));
+    let input = my_input!(5);
+    assert_eq!(Ok(Some(930)), problem(&input));
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(515, problem_part_2(MY_INPUT));
+    let input = my_input!(5);
+    assert_eq!(Ok(Some(515)), problem_part_2(&input));
   }
 
-  static MY_INPUT: &'static str = include_str!("day_5_input.txt");
+  #[test]
+  fn malformed_input() {
+    let err = problem("FBFBBFFRLR\nBFFXBBFRRR\n").unwrap_err();
+    assert_eq!((2, 4), (err.line, err.column));
+    let err = problem("FBFBBFFRL\n").unwrap_err();
+    assert_eq!("boarding pass is too short", err.message);
+  }
 }
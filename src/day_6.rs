@@ -1,54 +1,65 @@
+use crate::parse::{self, ParseError};
+use crate::{Example, Solution};
 use std::collections::BTreeSet;
 
-pub fn problem(input: &str) -> usize {
-  input
-    .split("\n\n")
-    .map(|group| {
-      let mut map = BTreeSet::new();
-      for line in group.lines() {
-        for char in line.chars() {
-          map.insert(char);
-        }
-      }
-      map.len()
+type Answers = BTreeSet<char>;
+
+fn parse_answers(line: &str) -> Result<Answers, ParseError> {
+  line
+    .trim_end()
+    .chars()
+    .enumerate()
+    .map(|(idx, c)| match c {
+      'a'..='z' => Ok(c),
+      _ => Err(ParseError::at(
+        idx + 1,
+        format!("expected a question from a to z, got {:?}", c),
+      )),
     })
-    .sum()
+    .collect()
+}
+
+fn parse_groups(input: &str) -> Result<Vec<Vec<Answers>>, ParseError> {
+  parse::records(input)
+    .into_iter()
+    .map(|(line, group)| parse::lines(group, parse_answers).map_err(|e| e.offset(line, 0)))
+    .collect()
 }
 
-pub fn problem_part_2(input: &str) -> usize {
-  input
-    .trim()
-    .split("\n\n")
-    .map(|group| {
-      let mut outer = None;
-      for line in group.lines() {
-        let map: BTreeSet<_> = line.chars().collect();
-        match outer {
-          None => outer = Some(map),
-          Some(m) => outer = Some(m.intersection(&map).map(|c| *c).collect()),
+pub fn problem(input: &str) -> Result<usize, ParseError> {
+  Ok(
+    parse_groups(input)?
+      .into_iter()
+      .map(|group| {
+        let mut map = BTreeSet::new();
+        for person in group {
+          map.extend(person);
         }
-      }
-      let outer = outer.unwrap();
-      println!(
-        "in: \n{:?}\n, {} answers were in common",
-        group,
-        outer.len()
-      );
-      outer.len()
-    })
-    .sum()
+        map.len()
+      })
+      .sum(),
+  )
 }
 
-#[cfg(test)]
-mod test {
-  use super::*;
+pub fn problem_part_2(input: &str) -> Result<usize, ParseError> {
+  Ok(
+    parse_groups(input)?
+      .into_iter()
+      .map(|group| {
+        let mut outer: Option<Answers> = None;
+        for map in group.iter() {
+          match outer {
+            None => outer = Some(map.clone()),
+            Some(m) => outer = Some(m.intersection(map).copied().collect()),
+          }
+        }
+        outer.map_or(0, |answers| answers.len())
+      })
+      .sum(),
+  )
+}
 
-  #[test]
-  fn examples() {
-    assert_eq!(
-      11,
-      problem(
-        "
+pub const EXAMPLE: &str = "\
 abc
 
 a
@@ -63,47 +74,64 @@ a
 a
 a
 
-b\
-        "
-      )
-    );
+b
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = usize;
+  const DAY: u8 = 6;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<usize, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = usize;
+  const DAY: u8 = 6;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<usize, ParseError> {
+    problem_part_2(text)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn examples() {
+    assert_eq!(Ok(11), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(6521, problem(MY_INPUT));
+    let input = my_input!(6);
+    assert_eq!(Ok(6521), problem(&input));
   }
 
   #[test]
   fn examples_part_2() {
-    assert_eq!(
-      6,
-      problem_part_2(
-        "
-abc
-
-a
-b
-c
-
-ab
-ac
-
-a
-a
-a
-a
-
-b\
-        "
-      )
-    );
+    assert_eq!(Ok(6), problem_part_2(EXAMPLE));
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(3305, problem_part_2(MY_INPUT));
+    let input = my_input!(6);
+    assert_eq!(Ok(3305), problem_part_2(&input));
   }
 
-  static MY_INPUT: &'static str = include_str!("day_6_input.txt");
+  #[test]
+  fn malformed_input() {
+    let err = problem("abc\n\na\nb!\n").unwrap_err();
+    assert_eq!((4, 2), (err.line, err.column));
+  }
 }
@@ -1,9 +1,11 @@
+use crate::parse::{self, ParseError};
+use crate::{Example, Solution};
 use regex::Regex;
 use Adjective::*;
 use Hue::*;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
-enum Hue {
+pub(crate) enum Hue {
   Aqua,
   Beige,
   Black,
@@ -39,47 +41,61 @@ enum Hue {
   Yellow,
 }
 impl Hue {
-  fn parse(s: &str) -> Self {
-    match s {
-      "aqua" => Aqua,
-      "beige" => Beige,
-      "black" => Black,
-      "blue" => Blue,
-      "bronze" => Bronze,
-      "brown" => Brown,
-      "chartreuse" => Chartreuse,
-      "coral" => Coral,
-      "crimson" => Crimson,
-      "cyan" => Cyan,
-      "fuchsia" => Fuchsia,
-      "gold" => Gold,
-      "gray" => Gray,
-      "green" => Green,
-      "indigo" => Indigo,
-      "lavender" => Lavender,
-      "lime" => Lime,
-      "magenta" => Magenta,
-      "maroon" => Maroon,
-      "olive" => Olive,
-      "orange" => Orange,
-      "plum" => Plum,
-      "purple" => Purple,
-      "red" => Red,
-      "salmon" => Salmon,
-      "silver" => Silver,
-      "tan" => Tan,
-      "teal" => Teal,
-      "tomato" => Tomato,
-      "turquoise" => Turquoise,
-      "violet" => Violet,
-      "white" => White,
-      "yellow" => Yellow,
-      _ => panic!("Unknown color hue: {}", s),
+  pub(crate) const ALL: [Hue; 33] = [
+    Aqua, Beige, Black, Blue, Bronze, Brown, Chartreuse, Coral, Crimson, Cyan, Fuchsia, Gold, Gray,
+    Green, Indigo, Lavender, Lime, Magenta, Maroon, Olive, Orange, Plum, Purple, Red, Salmon,
+    Silver, Tan, Teal, Tomato, Turquoise, Violet, White, Yellow,
+  ];
+
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Aqua => "aqua",
+      Beige => "beige",
+      Black => "black",
+      Blue => "blue",
+      Bronze => "bronze",
+      Brown => "brown",
+      Chartreuse => "chartreuse",
+      Coral => "coral",
+      Crimson => "crimson",
+      Cyan => "cyan",
+      Fuchsia => "fuchsia",
+      Gold => "gold",
+      Gray => "gray",
+      Green => "green",
+      Indigo => "indigo",
+      Lavender => "lavender",
+      Lime => "lime",
+      Magenta => "magenta",
+      Maroon => "maroon",
+      Olive => "olive",
+      Orange => "orange",
+      Plum => "plum",
+      Purple => "purple",
+      Red => "red",
+      Salmon => "salmon",
+      Silver => "silver",
+      Tan => "tan",
+      Teal => "teal",
+      Tomato => "tomato",
+      Turquoise => "turquoise",
+      Violet => "violet",
+      White => "white",
+      Yellow => "yellow",
     }
   }
+
+  fn parse(s: &str) -> Result<Self, ParseError> {
+    Self::ALL
+      .iter()
+      .copied()
+      .find(|hue| hue.name() == s)
+      .ok_or_else(|| ParseError::at(1, format!("unknown color hue: {}", s)))
+  }
 }
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
-enum Adjective {
+pub(crate) enum Adjective {
   Bright,
   Clear,
   Dark,
@@ -100,29 +116,41 @@ enum Adjective {
   Wavy,
 }
 impl Adjective {
-  fn parse(s: &str) -> Self {
-    match s {
-      "bright" => Bright,
-      "clear" => Clear,
-      "dark" => Dark,
-      "dim" => Dim,
-      "dotted" => Dotted,
-      "drab" => Drab,
-      "dull" => Dull,
-      "faded" => Faded,
-      "light" => Light,
-      "mirrored" => Mirrored,
-      "muted" => Muted,
-      "pale" => Pale,
-      "plaid" => Plaid,
-      "posh" => Posh,
-      "shiny" => Shiny,
-      "striped" => Striped,
-      "vibrant" => Vibrant,
-      "wavy" => Wavy,
-      _ => panic!("Unknown color adjective: {}", s),
+  pub(crate) const ALL: [Adjective; 18] = [
+    Bright, Clear, Dark, Dim, Dotted, Drab, Dull, Faded, Light, Mirrored, Muted, Pale, Plaid, Posh,
+    Shiny, Striped, Vibrant, Wavy,
+  ];
+
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Bright => "bright",
+      Clear => "clear",
+      Dark => "dark",
+      Dim => "dim",
+      Dotted => "dotted",
+      Drab => "drab",
+      Dull => "dull",
+      Faded => "faded",
+      Light => "light",
+      Mirrored => "mirrored",
+      Muted => "muted",
+      Pale => "pale",
+      Plaid => "plaid",
+      Posh => "posh",
+      Shiny => "shiny",
+      Striped => "striped",
+      Vibrant => "vibrant",
+      Wavy => "wavy",
     }
   }
+
+  fn parse(s: &str) -> Result<Self, ParseError> {
+    Self::ALL
+      .iter()
+      .copied()
+      .find(|adjective| adjective.name() == s)
+      .ok_or_else(|| ParseError::at(1, format!("unknown color adjective: {}", s)))
+  }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -131,11 +159,17 @@ struct Color {
   hue: Hue,
 }
 impl Color {
-  fn parse(text: &str) -> Self {
-    let idx = text.find(' ').unwrap();
-    let adjective = Adjective::parse(&text[0..idx]);
-    let hue = Hue::parse(&text[idx + 1..]);
-    Self { adjective, hue }
+  fn parse(text: &str) -> Result<Self, ParseError> {
+    let idx = text.find(' ').ok_or_else(|| {
+      ParseError::at(
+        1,
+        format!("expected an adjective and a hue, got {:?}", text),
+      )
+    })?;
+    let adjective = Adjective::parse(&text[0..idx])?;
+    let hue =
+      Hue::parse(&text[idx + 1..]).map_err(|e| e.offset(0, parse::column(text, idx + 1) - 1))?;
+    Ok(Self { adjective, hue })
   }
 }
 
@@ -147,26 +181,38 @@ struct Rule {
   contents: Vec<(usize, Color)>,
 }
 impl Rule {
-  fn parse(line: &str) -> Self {
+  fn parse(line: &str) -> Result<Self, ParseError> {
     lazy_static! {
-      static ref CONTAINER_RE: Regex = Regex::new(r"(.*?) bags contain ").unwrap();
+      static ref CONTAINER_RE: Regex = Regex::new(r"^(.*?) bags contain ").unwrap();
       static ref CONTAINED_RE: Regex = Regex::new(r"(\d+) (.*?) bag").unwrap();
     }
-    let capture = CONTAINER_RE.captures(line).unwrap();
-    let container = Color::parse(&capture[1]);
+    let at = |m: regex::Match| {
+      let column = parse::column(line, m.start()) - 1;
+      move |e: ParseError| e.offset(0, column)
+    };
+    let capture = CONTAINER_RE
+      .captures(line)
+      .ok_or_else(|| ParseError::at(1, "expected a rule, like `<color> bags contain ...`"))?;
+    let container = Color::parse(&capture[1])?;
     let contents = CONTAINED_RE
       .captures_iter(line)
-      .map(|capture| (capture[1].parse().unwrap(), Color::parse(&capture[2])))
-      .collect();
-    Rule {
+      .map(|capture| {
+        let count = capture.get(1).unwrap();
+        let color = capture.get(2).unwrap();
+        Ok((
+          parse::number(count.as_str()).map_err(at(count))?,
+          Color::parse(color.as_str()).map_err(at(color))?,
+        ))
+      })
+      .collect::<Result<_, ParseError>>()?;
+    Ok(Rule {
       container,
       contents,
-    }
+    })
   }
 }
 #[derive(Debug)]
 struct Ruleset {
-  rules: Vec<Rule>,
   contained: BTreeMap<Color, BTreeSet<Color>>,
   contents: BTreeMap<Color, Vec<(usize, Color)>>,
 }
@@ -185,7 +231,6 @@ impl Ruleset {
       contents.insert(rule.container, rule.contents.clone());
     }
     Self {
-      rules,
       contained,
       contents,
     }
@@ -235,17 +280,60 @@ impl Ruleset {
   }
 }
 
-pub fn problem(input: &str) -> usize {
-  let rules: Vec<_> = input.lines().map(|line| Rule::parse(line)).collect();
+const SHINY_GOLD: Color = Color {
+  adjective: Shiny,
+  hue: Gold,
+};
+
+pub fn problem(input: &str) -> Result<usize, ParseError> {
+  let rules = parse::lines(input, Rule::parse)?;
   let ruleset = Ruleset::new(rules);
-  let toplevel_options = ruleset.toplevel_bag_options(Color::parse("shiny gold"));
-  toplevel_options.len()
+  let toplevel_options = ruleset.toplevel_bag_options(SHINY_GOLD);
+  Ok(toplevel_options.len())
 }
 
-pub fn problem_part_2(input: &str) -> usize {
-  let rules: Vec<_> = input.lines().map(|line| Rule::parse(line)).collect();
+pub fn problem_part_2(input: &str) -> Result<usize, ParseError> {
+  let rules = parse::lines(input, Rule::parse)?;
   let ruleset = Ruleset::new(rules);
-  ruleset.nested_contents_count(Color::parse("shiny gold"))
+  Ok(ruleset.nested_contents_count(SHINY_GOLD))
+}
+
+pub const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = usize;
+  const DAY: u8 = 7;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<usize, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = usize;
+  const DAY: u8 = 7;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<usize, ParseError> {
+    problem_part_2(text)
+  }
 }
 
 #[cfg(test)]
@@ -254,34 +342,48 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(4, problem(EXAMPLES));
+    assert_eq!(Ok(4), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(274, problem(MY_INPUT));
+    let input = my_input!(7);
+    assert_eq!(Ok(274), problem(&input));
+  }
+
+  #[test]
+  fn every_color_word_parses() {
+    // In declaration order, so each variant is listed once.
+    assert!(Hue::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(Adjective::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    for hue in Hue::ALL.iter() {
+      assert_eq!(Ok(*hue), Hue::parse(hue.name()));
+    }
+    for adjective in Adjective::ALL.iter() {
+      assert_eq!(Ok(*adjective), Adjective::parse(adjective.name()));
+    }
+    assert!(Hue::parse("shiny").is_err());
   }
 
   #[test]
   fn examples_part_2() {
-    assert_eq!(32, problem_part_2(EXAMPLES))
+    assert_eq!(Ok(32), problem_part_2(EXAMPLE))
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(158730, problem_part_2(MY_INPUT))
+    let input = my_input!(7);
+    assert_eq!(Ok(158730), problem_part_2(&input))
   }
 
-  const EXAMPLES: &'static str = "\
-light red bags contain 1 bright white bag, 2 muted yellow bags.
-dark orange bags contain 3 bright white bags, 4 muted yellow bags.
-bright white bags contain 1 shiny gold bag.
-muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
-shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
-dark olive bags contain 3 faded blue bags, 4 dotted black bags.
-vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
-faded blue bags contain no other bags.
-dotted black bags contain no other bags.\
-  ";
-  static MY_INPUT: &'static str = include_str!("day_7_input.txt");
+  #[test]
+  fn malformed_input() {
+    let err =
+      problem("faded blue bags contain no other bags.\nlight red bags contain 1 bright wite bag.")
+        .unwrap_err();
+    assert_eq!((2, 33), (err.line, err.column));
+    assert_eq!("unknown color hue: wite", err.message);
+    let err = problem("light red bags hold 1 bright white bag.").unwrap_err();
+    assert_eq!((1, 1), (err.line, err.column));
+  }
 }
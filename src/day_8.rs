@@ -1,3 +1,5 @@
+use crate::parse::{self, ParseError};
+use crate::{Example, Solution};
 use std::collections::BTreeSet;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -7,13 +9,26 @@ enum Instruction {
   Nop(i64),
 }
 impl Instruction {
-  fn parse(s: &str) -> Self {
-    match &s[0..3] {
-      "acc" => Instruction::Acc(s[4..].parse().unwrap()),
-      "jmp" => Instruction::Jmp(s[4..].parse().unwrap()),
-      "nop" => Instruction::Nop(s[4..].parse().unwrap()),
-      _ => panic!("Unknown instruction: {}", s),
-    }
+  fn parse(s: &str) -> Result<Self, ParseError> {
+    let (operation, argument) = s.split_once(' ').ok_or_else(|| {
+      ParseError::at(
+        1,
+        format!("expected an operation and argument, got {:?}", s),
+      )
+    })?;
+    let argument = parse::number(argument)
+      .map_err(|e| e.offset(0, parse::column(s, operation.len() + 1) - 1))?;
+    Ok(match operation {
+      "acc" => Instruction::Acc(argument),
+      "jmp" => Instruction::Jmp(argument),
+      "nop" => Instruction::Nop(argument),
+      _ => {
+        return Err(ParseError::at(
+          1,
+          format!("unknown operation: {}", operation),
+        ))
+      }
+    })
   }
 }
 
@@ -36,16 +51,13 @@ struct Computer {
   instruction_pointer: usize,
 }
 impl Computer {
-  fn parse_from_instructions(instructions: &str) -> Self {
-    let instructions = instructions
-      .lines()
-      .map(|line| Instruction::parse(line))
-      .collect();
-    Self {
+  fn parse_from_instructions(instructions: &str) -> Result<Self, ParseError> {
+    let instructions = parse::lines(instructions, Instruction::parse)?;
+    Ok(Self {
       instructions,
       accumulator: 0,
       instruction_pointer: 0,
-    }
+    })
   }
 
   fn step(&mut self) -> StepStatus {
@@ -88,18 +100,18 @@ impl Computer {
         StepStatus::InProgress => continue,
       }
     }
-    return Completion::Looped;
+    Completion::Looped
   }
 }
 
-pub fn problem(input: &str) -> i64 {
-  let mut computer = Computer::parse_from_instructions(input.trim());
+pub fn problem(input: &str) -> Result<i64, ParseError> {
+  let mut computer = Computer::parse_from_instructions(input)?;
   computer.simple_infinite_loop_detector();
-  computer.accumulator
+  Ok(computer.accumulator)
 }
 
-pub fn problem_part_2(input: &str) -> Option<i64> {
-  let base_instructions = Computer::parse_from_instructions(input.trim()).instructions;
+pub fn problem_part_2(input: &str) -> Result<Option<i64>, ParseError> {
+  let base_instructions = Computer::parse_from_instructions(input)?.instructions;
   for idx in 0..base_instructions.len() {
     let mut instructions = base_instructions.clone();
     match instructions.get(idx) {
@@ -120,14 +132,52 @@ pub fn problem_part_2(input: &str) -> Option<i64> {
     };
     match computer.simple_infinite_loop_detector() {
       Completion::Finished => {
-        return Some(computer.accumulator);
+        return Ok(Some(computer.accumulator));
       }
       Completion::Looped | Completion::Broke => {
         continue;
       }
     }
   }
-  return None;
+  Ok(None)
+}
+
+pub const EXAMPLE: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = ();
+  type Answer = i64;
+  const DAY: u8 = 8;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<i64, ParseError> {
+    problem(text)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = ();
+  type Answer = Option<i64>;
+  const DAY: u8 = 8;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example::new(EXAMPLE));
+
+  fn solve(text: &str, _input: ()) -> Result<Option<i64>, ParseError> {
+    problem_part_2(text)
+  }
 }
 
 #[cfg(test)]
@@ -136,34 +186,31 @@ mod test {
 
   #[test]
   fn examples() {
-    assert_eq!(5, problem(EXAMPLES));
+    assert_eq!(Ok(5), problem(EXAMPLE));
   }
 
   #[test]
   fn my_input() {
-    assert_eq!(1501, problem(MY_INPUT));
+    let input = my_input!(8);
+    assert_eq!(Ok(1501), problem(&input));
   }
 
   #[test]
   fn examples_part_2() {
-    assert_eq!(Some(8), problem_part_2(EXAMPLES))
+    assert_eq!(Ok(Some(8)), problem_part_2(EXAMPLE))
   }
 
   #[test]
   fn part_2_my_input() {
-    assert_eq!(Some(509), problem_part_2(MY_INPUT))
+    let input = my_input!(8);
+    assert_eq!(Ok(Some(509)), problem_part_2(&input))
   }
 
-  const EXAMPLES: &'static str = "\
-nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6
-  ";
-  static MY_INPUT: &'static str = include_str!("day_8_input.txt");
+  #[test]
+  fn malformed_input() {
+    let err = problem("nop +0\nacc 1x\n").unwrap_err();
+    assert_eq!((2, 5), (err.line, err.column));
+    let err = problem("nop +0\nhcf +1\n").unwrap_err();
+    assert_eq!("unknown operation: hcf", err.message);
+  }
 }
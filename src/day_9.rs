@@ -1,3 +1,5 @@
+use crate::parse::{self, ParseError};
+use crate::{Args, Example, FromArgs, Solution};
 use std::collections::{BTreeSet, VecDeque};
 
 struct Protocol {
@@ -38,22 +40,14 @@ impl Protocol {
   }
 }
 
-pub fn problem(input: &str, window_size: usize) -> i64 {
+pub fn problem(input: &str, window_size: usize) -> Result<Option<i64>, ParseError> {
   let mut protocol = Protocol::new(window_size);
-  input
-    .trim()
-    .lines()
-    .map(|line| line.parse().unwrap())
-    .find(|v| !protocol.is_valid(*v))
-    .unwrap()
+  let nums: Vec<i64> = parse::lines(input, parse::number)?;
+  Ok(nums.into_iter().find(|v| !protocol.is_valid(*v)))
 }
 
-pub fn problem_part_2(input: &str, target: i64) -> i64 {
-  let nums: Vec<i64> = input
-    .trim()
-    .lines()
-    .map(|line| line.parse().unwrap())
-    .collect();
+pub fn problem_part_2(input: &str, target: i64) -> Result<Option<i64>, ParseError> {
+  let nums: Vec<i64> = parse::lines(input, parse::number)?;
   let mut range: Option<(usize, usize)> = None;
   'outer: for lower_idx in 0..nums.len() {
     for upper_idx in (lower_idx + 1)..nums.len() {
@@ -70,36 +64,74 @@ pub fn problem_part_2(input: &str, target: i64) -> i64 {
       }
     }
   }
-  let (lower, upper) = range.unwrap();
-  nums[lower..=upper].iter().min().unwrap() + nums[lower..=upper].iter().max().unwrap()
+  Ok(range.map(|(lower, upper)| weakness(&nums[lower..=upper])))
 }
 
-#[cfg(test)]
-mod test {
-  use super::*;
-
-  #[test]
-  fn examples() {
-    assert_eq!(127, problem(EXAMPLES, 5));
+/// Like `problem_part_2`, but keeps a running sum over a window that only
+/// ever moves forward, so it's linear rather than quadratic. Relies on every
+/// number being positive, as they are in the puzzle.
+pub fn problem_part_2_sliding_window(input: &str, target: i64) -> Result<Option<i64>, ParseError> {
+  let nums: Vec<i64> = parse::lines(input, parse::number)?;
+  let mut lower_idx = 0;
+  let mut sum = 0;
+  for upper_idx in 0..nums.len() {
+    sum += nums[upper_idx];
+    while sum > target && lower_idx < upper_idx {
+      sum -= nums[lower_idx];
+      lower_idx += 1;
+    }
+    if sum == target && lower_idx < upper_idx {
+      return Ok(Some(weakness(&nums[lower_idx..=upper_idx])));
+    }
   }
+  Ok(None)
+}
 
-  #[test]
-  fn my_input() {
-    assert_eq!(507622668, problem(MY_INPUT, 25));
+fn weakness(range: &[i64]) -> i64 {
+  range.iter().min().unwrap() + range.iter().max().unwrap()
+}
+
+/// Day 9's arguments: how many of the previous numbers each new number must
+/// be the sum of two of.
+pub struct Preamble {
+  pub window_size: usize,
+}
+impl FromArgs for Preamble {
+  const NAMES: &'static [&'static str] = &["window-size"];
+
+  fn from_args(args: &Args) -> Result<Self, String> {
+    Ok(Self {
+      window_size: args.get("window-size")?.unwrap_or(25),
+    })
   }
+}
 
-  #[test]
-  fn examples_part_2() {
-    assert_eq!(62, problem_part_2(EXAMPLES, 127));
+/// Part 2's arguments: the invalid number to find a contiguous range summing
+/// to. When no target is given, it's part 1's answer for the same preamble.
+pub struct Weakness {
+  pub preamble: Preamble,
+  pub target: Option<i64>,
+}
+impl Weakness {
+  fn target(&self, text: &str) -> Result<Option<i64>, ParseError> {
+    match self.target {
+      Some(target) => Ok(Some(target)),
+      None => problem(text, self.preamble.window_size),
+    }
   }
+}
+impl FromArgs for Weakness {
+  const NAMES: &'static [&'static str] = &["window-size", "target"];
 
-  #[cfg(not(debug_assertions))]
-  #[test]
-  fn part_2_my_input() {
-    assert_eq!(76688505, problem_part_2(MY_INPUT, 507622668));
+  fn from_args(args: &Args) -> Result<Self, String> {
+    Ok(Self {
+      preamble: Preamble::from_args(args)?,
+      target: args.get("target")?,
+    })
   }
+}
 
-  const EXAMPLES: &'static str = "\
+pub const EXAMPLE: &str = "\
 35
 20
 15
@@ -120,6 +152,110 @@ mod test {
 277
 309
 576
-  ";
-  static MY_INPUT: &'static str = include_str!("day_9_input.txt");
+";
+
+pub struct Part1;
+impl Solution for Part1 {
+  type Input = Preamble;
+  type Answer = Option<i64>;
+  const DAY: u8 = 9;
+  const PART: u8 = 1;
+  const EXAMPLE: Option<Example> = Some(Example {
+    text: EXAMPLE,
+    args: &[("window-size", "5")],
+  });
+
+  fn solve(text: &str, input: Preamble) -> Result<Option<i64>, ParseError> {
+    problem(text, input.window_size)
+  }
+}
+
+pub struct Part2;
+impl Solution for Part2 {
+  type Input = Weakness;
+  type Answer = Option<i64>;
+  const DAY: u8 = 9;
+  const PART: u8 = 2;
+  const EXAMPLE: Option<Example> = Some(Example {
+    text: EXAMPLE,
+    args: &[("window-size", "5")],
+  });
+
+  fn solve(text: &str, input: Weakness) -> Result<Option<i64>, ParseError> {
+    match input.target(text)? {
+      Some(target) => problem_part_2(text, target),
+      None => Ok(None),
+    }
+  }
+}
+
+pub struct Part2SlidingWindow;
+impl Solution for Part2SlidingWindow {
+  type Input = Weakness;
+  type Answer = Option<i64>;
+  const DAY: u8 = 9;
+  const PART: u8 = 2;
+  const NAME: &'static str = "sliding window";
+  const EXAMPLE: Option<Example> = Part2::EXAMPLE;
+
+  fn solve(text: &str, input: Weakness) -> Result<Option<i64>, ParseError> {
+    match input.target(text)? {
+      Some(target) => problem_part_2_sliding_window(text, target),
+      None => Ok(None),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn examples() {
+    assert_eq!(Ok(Some(127)), problem(EXAMPLE, 5));
+  }
+
+  #[test]
+  fn my_input() {
+    let input = my_input!(9);
+    assert_eq!(Ok(Some(507622668)), problem(&input, 25));
+  }
+
+  #[test]
+  fn examples_part_2() {
+    assert_eq!(Ok(Some(62)), problem_part_2(EXAMPLE, 127));
+  }
+
+  #[cfg(not(debug_assertions))]
+  #[test]
+  fn part_2_my_input() {
+    let input = my_input!(9);
+    assert_eq!(Ok(Some(76688505)), problem_part_2(&input, 507622668));
+  }
+
+  #[test]
+  fn sliding_window() {
+    assert_eq!(Ok(Some(62)), problem_part_2_sliding_window(EXAMPLE, 127));
+    // A single number equal to the target doesn't count as a range.
+    assert_eq!(Ok(None), problem_part_2_sliding_window("1\n5\n1\n", 5));
+    assert_eq!(
+      Ok(Some(5)),
+      problem_part_2_sliding_window("1\n5\n1\n2\n3\n", 5)
+    );
+  }
+
+  #[test]
+  fn sliding_window_my_input() {
+    let input = my_input!(9);
+    assert_eq!(
+      Ok(Some(76688505)),
+      problem_part_2_sliding_window(&input, 507622668)
+    );
+  }
+
+  #[test]
+  fn malformed_input() {
+    let err = problem("35\n20\n-\n", 5).unwrap_err();
+    assert_eq!((3, 1), (err.line, err.column));
+  }
 }
@@ -0,0 +1,619 @@
+use crate::day_7::{Adjective, Hue};
+use crate::Args;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+
+/// A small seedable random number generator (SplitMix64). Generated inputs
+/// only need to be reproducible from their seed, not unpredictable.
+#[derive(Clone, Debug)]
+pub struct Rng {
+  state: u64,
+}
+impl Rng {
+  pub fn new(seed: u64) -> Self {
+    Self { state: seed }
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /// A number in `low..=high`.
+  pub fn range(&mut self, low: i64, high: i64) -> i64 {
+    low + (self.next_u64() % ((high - low) as u64 + 1)) as i64
+  }
+
+  /// A number in `0..n`.
+  pub fn below(&mut self, n: usize) -> usize {
+    (self.next_u64() % n as u64) as usize
+  }
+
+  /// True with a probability of one in `n`.
+  pub fn one_in(&mut self, n: usize) -> bool {
+    self.below(n) == 0
+  }
+
+  pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+    &items[self.below(items.len())]
+  }
+
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      items.swap(i, self.below(i + 1));
+    }
+  }
+
+  pub fn letter(&mut self) -> char {
+    (b'a' + self.below(26) as u8) as char
+  }
+}
+
+/// A generated puzzle input, along with the answers it was built to have.
+pub struct Generated {
+  pub text: String,
+  /// The arguments to run the puzzle with.
+  pub args: Args,
+  /// The answers to parts 1 and 2, formatted as `Puzzle::run` formats them.
+  pub answers: [String; 2],
+}
+impl Generated {
+  fn new(text: String, part_1: impl Debug, part_2: impl Debug) -> Self {
+    Self {
+      text,
+      args: Args::new(),
+      answers: [format!("{:?}", part_1), format!("{:?}", part_2)],
+    }
+  }
+}
+
+/// Generates an input for `day` with roughly `size` lines or records. Some
+/// days can't go arbitrarily large without their answers overflowing, and
+/// cap `size`.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<Generated> {
+  let rng = &mut Rng::new(seed);
+  Some(match day {
+    1 => day_1(rng, size),
+    2 => day_2(rng, size),
+    3 => day_3(rng, size),
+    4 => day_4(rng, size),
+    5 => day_5(rng, size),
+    6 => day_6(rng, size),
+    7 => day_7(rng, size),
+    8 => day_8(rng, size),
+    9 => day_9(rng, size),
+    10 => day_10(rng, size),
+    _ => return None,
+  })
+}
+
+fn lines<T: ToString>(items: impl IntoIterator<Item = T>) -> String {
+  let mut text = String::new();
+  for item in items {
+    text.push_str(&item.to_string());
+    text.push('\n');
+  }
+  text
+}
+
+/// An expense report with exactly one pair and one triple of entries that
+/// sum to 2020. Every other entry is over 2020, so can't be part of either.
+pub fn day_1(rng: &mut Rng, size: usize) -> Generated {
+  let planted = loop {
+    let a = rng.range(1, 1009);
+    let x = rng.range(1, 672);
+    let y = rng.range(x + 1, (2020 - x - 1) / 2);
+    let planted = [a, 2020 - a, x, y, 2020 - x - y];
+    if count_sums(&planted, 2, 2020) == 1 && count_sums(&planted, 3, 2020) == 1 {
+      break planted;
+    }
+  };
+  let mut entries = planted.to_vec();
+  while entries.len() < size {
+    entries.push(rng.range(2021, 9999));
+  }
+  rng.shuffle(&mut entries);
+  Generated::new(
+    lines(entries),
+    Some(planted[0] * planted[1]),
+    Some(planted[2] * planted[3] * planted[4]),
+  )
+}
+
+/// How many ways `k` distinct entries can be picked from `entries` to sum
+/// to `target`.
+fn count_sums(entries: &[i64], k: usize, target: i64) -> usize {
+  if k == 0 {
+    return if target == 0 { 1 } else { 0 };
+  }
+  (0..entries.len())
+    .map(|i| count_sums(&entries[i + 1..], k - 1, target - entries[i]))
+    .sum()
+}
+
+/// Password policies and passwords, with the policy's letter common enough
+/// in the password that plenty are valid under either interpretation.
+pub fn day_2(rng: &mut Rng, size: usize) -> Generated {
+  let mut text = String::new();
+  let mut valid_counts = 0;
+  let mut valid_positions = 0;
+  for _ in 0..size {
+    let lower = rng.range(1, 5) as usize;
+    let upper = rng.range(lower as i64 + 1, lower as i64 + 8) as usize;
+    let letter = rng.letter();
+    let length = rng.range(upper as i64, upper as i64 + 6) as usize;
+    let password: Vec<char> = (0..length)
+      .map(|_| if rng.one_in(3) { letter } else { rng.letter() })
+      .collect();
+
+    let count = password.iter().filter(|c| **c == letter).count();
+    if lower <= count && count <= upper {
+      valid_counts += 1;
+    }
+    if (password[lower - 1] == letter) != (password[upper - 1] == letter) {
+      valid_positions += 1;
+    }
+    let password: String = password.into_iter().collect();
+    text.push_str(&format!("{}-{} {}: {}\n", lower, upper, letter, password));
+  }
+  Generated::new(text, Some(valid_counts), Some(valid_positions))
+}
+
+/// A forest `size` rows tall, as wide as the puzzle's, with a tree on about
+/// a quarter of the squares.
+pub fn day_3(rng: &mut Rng, size: usize) -> Generated {
+  let width = 31;
+  let rows: Vec<Vec<bool>> = (0..size)
+    .map(|_| (0..width).map(|_| rng.one_in(4)).collect())
+    .collect();
+  let trees = |(dx, dy): (usize, usize)| {
+    (0..)
+      .map(|step| (step * dx % width, step * dy))
+      .take_while(|(_, y)| *y < rows.len())
+      .filter(|(x, y)| rows[*y][*x])
+      .count()
+  };
+  let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+  let product = slopes.iter().try_fold(1u128, |product, slope| {
+    product.checked_mul(trees(*slope) as u128)
+  });
+  let text = lines(rows.iter().map(|row| {
+    row
+      .iter()
+      .map(|tree| if *tree { '#' } else { '.' })
+      .collect::<String>()
+  }));
+  Generated::new(text, trees((3, 1)), product)
+}
+
+const PASSPORT_KEYS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+fn valid_passport_value(rng: &mut Rng, key: &str) -> String {
+  match key {
+    "byr" => rng.range(1920, 2002).to_string(),
+    "iyr" => rng.range(2010, 2020).to_string(),
+    "eyr" => rng.range(2020, 2030).to_string(),
+    "hgt" if rng.one_in(2) => format!("{}cm", rng.range(150, 193)),
+    "hgt" => format!("{}in", rng.range(59, 76)),
+    "hcl" => format!("#{:06x}", rng.below(0x100_0000)),
+    "ecl" => rng.choose(&EYE_COLORS).to_string(),
+    "pid" => format!("{:09}", rng.below(1_000_000_000)),
+    "cid" => rng.range(100, 999).to_string(),
+    _ => unreachable!("unknown passport key: {}", key),
+  }
+}
+
+fn invalid_passport_value(rng: &mut Rng, key: &str) -> String {
+  let low_or_high = |rng: &mut Rng, low: (i64, i64), high: (i64, i64)| {
+    if rng.one_in(2) {
+      rng.range(low.0, low.1)
+    } else {
+      rng.range(high.0, high.1)
+    }
+  };
+  match key {
+    "byr" => low_or_high(rng, (1900, 1919), (2003, 2030)).to_string(),
+    "iyr" => low_or_high(rng, (1990, 2009), (2021, 2030)).to_string(),
+    "eyr" => low_or_high(rng, (2000, 2019), (2031, 2040)).to_string(),
+    "hgt" => match rng.below(3) {
+      0 => format!("{}cm", low_or_high(rng, (100, 149), (194, 220))),
+      1 => format!("{}in", low_or_high(rng, (40, 58), (77, 90))),
+      _ => rng.range(100, 200).to_string(),
+    },
+    "hcl" if rng.one_in(2) => format!("{:06x}", rng.below(0x100_0000)),
+    "hcl" => format!("#{:05x}z", rng.below(0x10_0000)),
+    "ecl" => rng.choose(&["zzz", "xry", "blk", "red"]).to_string(),
+    "pid" if rng.one_in(2) => format!("{:08}", rng.below(100_000_000)),
+    "pid" => format!("{:010}", rng.below(10_000_000_000)),
+    _ => unreachable!("no invalid values for passport key: {}", key),
+  }
+}
+
+/// Batches where about half the passports are valid, a quarter are missing
+/// a required field, and a quarter have one required field with an invalid
+/// value.
+pub fn day_4(rng: &mut Rng, size: usize) -> Generated {
+  let mut records = Vec::new();
+  let mut complete = 0;
+  let mut valid = 0;
+  for _ in 0..size {
+    let mut fields: Vec<(&str, String)> = PASSPORT_KEYS
+      .iter()
+      .map(|key| (*key, valid_passport_value(rng, key)))
+      .collect();
+    if rng.one_in(2) {
+      fields.push(("cid", valid_passport_value(rng, "cid")));
+    }
+    match rng.below(4) {
+      0 => {
+        fields.remove(rng.below(PASSPORT_KEYS.len()));
+      }
+      1 => {
+        complete += 1;
+        let idx = rng.below(PASSPORT_KEYS.len());
+        fields[idx].1 = invalid_passport_value(rng, fields[idx].0);
+      }
+      _ => {
+        complete += 1;
+        valid += 1;
+      }
+    }
+    rng.shuffle(&mut fields);
+
+    let mut record = String::new();
+    for (idx, (key, value)) in fields.iter().enumerate() {
+      if idx > 0 {
+        record.push(if rng.one_in(3) { '\n' } else { ' ' });
+      }
+      record.push_str(&format!("{}:{}", key, value));
+    }
+    records.push(record);
+  }
+  Generated::new(records.join("\n\n") + "\n", complete, valid)
+}
+
+/// Every boarding pass for a run of `size` consecutive seats but one, which
+/// is ours. Capped at 1000 seats, as the plane only has 1024.
+pub fn day_5(rng: &mut Rng, size: usize) -> Generated {
+  let size = size.clamp(3, 1000) as i64;
+  let first = rng.range(0, 1023 - size);
+  let last = first + size - 1;
+  let mine = rng.range(first + 1, last - 1);
+  let mut passes: Vec<String> = (first..=last)
+    .filter(|id| *id != mine)
+    .map(|id| {
+      let row = (0..7).rev().map(|bit| {
+        if (id >> 3) & (1 << bit) != 0 {
+          'B'
+        } else {
+          'F'
+        }
+      });
+      let column = (0..3)
+        .rev()
+        .map(|bit| if id & (1 << bit) != 0 { 'R' } else { 'L' });
+      row.chain(column).collect()
+    })
+    .collect();
+  rng.shuffle(&mut passes);
+  Generated::new(lines(passes), Some(last as usize), Some(mine as usize))
+}
+
+/// `size` groups of one to five people, each answering yes to a random
+/// handful of questions.
+pub fn day_6(rng: &mut Rng, size: usize) -> Generated {
+  let mut groups = Vec::new();
+  let mut anyone = 0;
+  let mut everyone = 0;
+  for _ in 0..size {
+    let people: Vec<BTreeSet<char>> = (0..rng.range(1, 5))
+      .map(|_| {
+        let mut answers: BTreeSet<char> = ('a'..='z').filter(|_| rng.one_in(3)).collect();
+        answers.insert(rng.letter());
+        answers
+      })
+      .collect();
+    let union: BTreeSet<char> = people.iter().flatten().copied().collect();
+    anyone += union.len();
+    everyone += union
+      .iter()
+      .filter(|c| people.iter().all(|p| p.contains(c)))
+      .count();
+    let people: Vec<String> = people
+      .into_iter()
+      .map(|answers| {
+        let mut answers: Vec<char> = answers.into_iter().collect();
+        rng.shuffle(&mut answers);
+        answers.into_iter().collect()
+      })
+      .collect();
+    groups.push(people.join("\n"));
+  }
+  Generated::new(groups.join("\n\n") + "\n", anyone, everyone)
+}
+
+/// Rules for `size` bag colors, capped at the 594 colors there are names
+/// for. Bags only contain bags of colors later in a random order, so the
+/// rules never loop, and shiny gold comes near the end so that the count of
+/// bags inside it stays small.
+pub fn day_7(rng: &mut Rng, size: usize) -> Generated {
+  let mut colors: Vec<String> = Adjective::ALL
+    .iter()
+    .flat_map(|adjective| {
+      let adjective = adjective.name();
+      Hue::ALL
+        .iter()
+        .map(move |hue| format!("{} {}", adjective, hue.name()))
+    })
+    .filter(|color| color != "shiny gold")
+    .collect();
+  rng.shuffle(&mut colors);
+  colors.truncate(size.clamp(1, colors.len() + 1) - 1);
+  let gold = colors.len() - colors.len().min(6);
+  colors.insert(gold, "shiny gold".to_string());
+
+  let mut contents: Vec<Vec<(usize, usize)>> = Vec::new();
+  for idx in 0..colors.len() {
+    let mut inside = BTreeMap::new();
+    if idx + 1 < colors.len() {
+      for _ in 0..rng.below(5) {
+        let color = rng.range(idx as i64 + 1, colors.len() as i64 - 1) as usize;
+        inside.insert(color, rng.range(1, 3) as usize);
+      }
+    }
+    contents.push(inside.into_iter().collect());
+  }
+
+  let mut containers = BTreeSet::new();
+  let mut pending = vec![gold];
+  while let Some(target) = pending.pop() {
+    for (idx, inside) in contents.iter().enumerate() {
+      if inside.iter().any(|(color, _)| *color == target) && containers.insert(idx) {
+        pending.push(idx);
+      }
+    }
+  }
+  // Colors only contain later colors, so working backwards means everything
+  // inside a bag has already been counted.
+  let mut nested = vec![0; colors.len()];
+  for idx in (0..colors.len()).rev() {
+    nested[idx] = contents[idx]
+      .iter()
+      .map(|(color, count)| count * (1 + nested[*color]))
+      .sum();
+  }
+
+  let mut rules: Vec<String> = contents
+    .iter()
+    .enumerate()
+    .map(|(idx, inside)| {
+      let inside: Vec<String> = inside
+        .iter()
+        .map(|(color, count)| {
+          let plural = if *count == 1 { "" } else { "s" };
+          format!("{} {} bag{}", count, colors[*color], plural)
+        })
+        .collect();
+      let inside = if inside.is_empty() {
+        "no other bags".to_string()
+      } else {
+        inside.join(", ")
+      };
+      format!("{} bags contain {}.", colors[idx], inside)
+    })
+    .collect();
+  rng.shuffle(&mut rules);
+  Generated::new(lines(rules), containers.len(), nested[gold])
+}
+
+/// Programs of `size` instructions that loop because of a single backwards
+/// `jmp`. Every other jump goes forwards without passing the faulty one, and
+/// `nop`s would do the same if they were jumps, so fixing it is the only
+/// change that lets the program terminate.
+pub fn day_8(rng: &mut Rng, size: usize) -> Generated {
+  let length = size.max(1) as i64;
+  let faulty = rng.range(0, length - 1);
+  let mut program: Vec<(&str, i64)> = (0..length)
+    .map(|idx| {
+      let limit = if idx < faulty { faulty } else { length };
+      match rng.below(4) {
+        0 | 1 => ("acc", rng.range(-50, 50)),
+        2 if idx != faulty => ("nop", rng.range(-idx, limit - idx)),
+        _ if idx < limit - 1 => ("jmp", rng.range(1, limit - idx)),
+        _ => ("nop", 0),
+      }
+    })
+    .collect();
+
+  let mut path = Vec::new();
+  let mut idx = 0;
+  while idx != faulty {
+    path.push(idx);
+    idx += match program[idx as usize] {
+      ("jmp", offset) => offset,
+      _ => 1,
+    };
+  }
+  path.push(faulty);
+  let looped_at = *rng.choose(&path);
+  program[faulty as usize] = ("jmp", looped_at - faulty);
+
+  let accumulate = |indices: &mut dyn Iterator<Item = i64>| -> i64 {
+    indices
+      .map(|idx| match program[idx as usize] {
+        ("acc", value) => value,
+        _ => 0,
+      })
+      .sum()
+  };
+  let before_loop = accumulate(&mut path.iter().copied());
+  let mut after_fix = Vec::new();
+  let mut idx = faulty + 1;
+  while idx < length {
+    after_fix.push(idx);
+    idx += match program[idx as usize] {
+      ("jmp", offset) => offset,
+      _ => 1,
+    };
+  }
+  let fixed = before_loop + accumulate(&mut after_fix.into_iter());
+
+  let text = lines(
+    program
+      .iter()
+      .map(|(operation, argument)| format!("{} {:+}", operation, argument)),
+  );
+  Generated::new(text, before_loop, Some(fixed))
+}
+
+/// XMAS data with a 25 number preamble, where each later number is the sum
+/// of two of the smallest numbers before it, until the last, which is the
+/// sum of the first few numbers instead. Numbers at least double every 25
+/// steps, so `size` is capped at 1000 to keep them well within an `i64`.
+pub fn day_9(rng: &mut Rng, size: usize) -> Generated {
+  let window = 25;
+  let size = size.clamp(window + 2, 1000);
+  loop {
+    let mut preamble: Vec<i64> = (1..=100).collect();
+    rng.shuffle(&mut preamble);
+    let mut nums: Vec<i64> = preamble[..window].to_vec();
+    while nums.len() < size - 1 {
+      let mut smallest: Vec<i64> = nums[nums.len() - window..].to_vec();
+      smallest.sort_unstable();
+      smallest.dedup();
+      let a = rng.below(4);
+      let b = (a + 1 + rng.below(3)) % 4;
+      nums.push(smallest[a] + smallest[b]);
+    }
+
+    let last_window = &nums[nums.len() - window..];
+    let is_pair_sum = |target: i64| {
+      last_window
+        .iter()
+        .any(|a| last_window.iter().any(|b| a != b && a + b == target))
+    };
+    let end = rng.range(1, window as i64 - 1) as usize;
+    let target: i64 = nums[..=end].iter().sum();
+    if is_pair_sum(target) {
+      continue;
+    }
+    let range = &nums[..=end];
+    let weakness = range.iter().min().unwrap() + range.iter().max().unwrap();
+    nums.push(target);
+
+    let mut generated = Generated::new(lines(nums), Some(target), Some(weakness));
+    generated.args.set("window-size", &window.to_string());
+    return generated;
+  }
+}
+
+/// Chains of `size` adapters made of runs of 1 jolt steps between 3 jolt
+/// steps, like the puzzle's. Each run multiplies the number of arrangements,
+/// so runs are kept short once more would overflow.
+pub fn day_10(rng: &mut Rng, size: usize) -> Generated {
+  // How many ways a run of this many 1 jolt steps can be arranged, with its
+  // ends fixed by the 3 jolt steps either side.
+  const ARRANGEMENTS: [u64; 5] = [1, 1, 2, 4, 7];
+  let mut steps = Vec::new();
+  let mut arrangements: u64 = 1;
+  while steps.len() < size {
+    let mut run = rng.range(1, 4) as usize;
+    run = run.min(size - steps.len());
+    match arrangements.checked_mul(ARRANGEMENTS[run] * 16) {
+      Some(_) => arrangements *= ARRANGEMENTS[run],
+      None => run = 1,
+    }
+    steps.extend(std::iter::repeat_n(1, run));
+    if steps.len() < size {
+      steps.push(3);
+    }
+  }
+  let ones = steps.iter().filter(|s| **s == 1).count() as u64;
+  let threes = steps.iter().filter(|s| **s == 3).count() as u64 + 1;
+
+  let mut adapters: Vec<u64> = steps
+    .iter()
+    .scan(0, |jolts, step| {
+      *jolts += step;
+      Some(*jolts)
+    })
+    .collect();
+  rng.shuffle(&mut adapters);
+  Generated::new(lines(adapters), ones * threes, arrangements)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::find;
+
+  #[test]
+  fn answers_match_solutions() {
+    for day in 1..=10 {
+      for size in [1, 10, 200].iter() {
+        for seed in 0..5 {
+          let generated = generate(day, *size, seed).unwrap();
+          for part in 1..=2 {
+            let result = find(day, part)
+              .unwrap()
+              .run(&generated.text, &generated.args);
+            assert_eq!(
+              Ok(generated.answers[part as usize - 1].clone()),
+              result,
+              "day {} part {}, size {}, seed {}",
+              day,
+              part,
+              size,
+              seed
+            );
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn seeds_are_reproducible() {
+    let text = |seed| generate(4, 50, seed).unwrap().text;
+    assert_eq!(text(7), text(7));
+    assert_ne!(text(7), text(8));
+  }
+
+  #[test]
+  fn largest_sizes() {
+    let generated = generate(9, 1_000_000, 1).unwrap();
+    assert_eq!(1000, generated.text.lines().count());
+    let generated = generate(10, 10_000, 1).unwrap();
+    assert_eq!(10_000, generated.text.lines().count());
+  }
+
+  #[test]
+  fn tall_forests() {
+    // As tall as the benchmarks' map, where the product of tree counts is
+    // too big for a `u64`.
+    let generated = generate(3, 100_000, 0x2020).unwrap();
+    let product: u128 = generated.answers[1]
+      .trim_start_matches("Some(")
+      .trim_end_matches(')')
+      .parse()
+      .unwrap();
+    assert!(product > u64::MAX as u128);
+    for part in 1..=2 {
+      for puzzle in crate::PUZZLES
+        .iter()
+        .filter(|p| (p.day, p.part) == (3, part))
+      {
+        assert_eq!(
+          Ok(generated.answers[part as usize - 1].clone()),
+          puzzle.run(&generated.text, &generated.args),
+          "{}",
+          puzzle.name
+        );
+      }
+    }
+  }
+}
@@ -0,0 +1,304 @@
+use crate::parse::ParseError;
+
+/// An `(x, y)` position in a grid, with `(0, 0)` at the top left.
+pub type Point = (usize, usize);
+
+/// What happens past the sides of a grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Edges {
+  /// Nothing is there.
+  Bounded,
+  /// Each row repeats forever to the right and left, like day 3's forest.
+  /// There's nothing above or below.
+  WrapHorizontal,
+  /// Rows and columns both repeat forever.
+  Torus,
+}
+
+/// A rectangle of cells, stored row after row in one buffer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+  edges: Edges,
+}
+impl<T> Grid<T> {
+  /// Panics unless there are `width * height` cells.
+  pub fn new(width: usize, height: usize, cells: Vec<T>, edges: Edges) -> Self {
+    assert_eq!(
+      width * height,
+      cells.len(),
+      "a {}x{} grid needs {} cells",
+      width,
+      height,
+      width * height
+    );
+    Self {
+      width,
+      height,
+      cells,
+      edges,
+    }
+  }
+
+  pub fn filled(width: usize, height: usize, value: T, edges: Edges) -> Self
+  where
+    T: Clone,
+  {
+    Self::new(width, height, vec![value; width * height], edges)
+  }
+
+  /// Parses a map with one row per non-blank line, using `cell` to say what
+  /// each char means, or None when it means nothing. Every row must be as
+  /// wide as the first.
+  pub fn parse(
+    input: &str,
+    edges: Edges,
+    cell: impl FnMut(char) -> Option<T>,
+  ) -> Result<Self, ParseError>
+  where
+    T: Clone,
+  {
+    let mut cells = Vec::new();
+    let (width, height) = parse_rows(input, cell, |row| cells.extend_from_slice(row))?;
+    Ok(Self::new(width, height, cells, edges))
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn edges(&self) -> Edges {
+    self.edges
+  }
+
+  pub fn with_edges(self, edges: Edges) -> Self {
+    Self { edges, ..self }
+  }
+
+  /// Where `point` ends up after wrapping around any edges that wrap, or
+  /// None if it's off the grid.
+  pub fn wrap(&self, (x, y): Point) -> Option<Point> {
+    if self.width == 0 || self.height == 0 {
+      return None;
+    }
+    let (x, y) = match self.edges {
+      Edges::Bounded => (x, y),
+      Edges::WrapHorizontal => (x % self.width, y),
+      Edges::Torus => (x % self.width, y % self.height),
+    };
+    if x < self.width && y < self.height {
+      Some((x, y))
+    } else {
+      None
+    }
+  }
+
+  pub fn get(&self, point: Point) -> Option<&T> {
+    let (x, y) = self.wrap(point)?;
+    Some(&self.cells[y * self.width + x])
+  }
+
+  pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+    let (x, y) = self.wrap(point)?;
+    Some(&mut self.cells[y * self.width + x])
+  }
+
+  /// The points above, below, left and right of `point`.
+  pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+    const OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+    self.neighbors(point, &OFFSETS)
+  }
+
+  /// The points around `point`, including diagonals.
+  pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+    const OFFSETS: [(isize, isize); 8] = [
+      (-1, -1),
+      (0, -1),
+      (1, -1),
+      (-1, 0),
+      (1, 0),
+      (-1, 1),
+      (0, 1),
+      (1, 1),
+    ];
+    self.neighbors(point, &OFFSETS)
+  }
+
+  /// Neighbors that are on the grid, after wrapping. On grids smaller than
+  /// three squares across that wrap, the same point can come up twice.
+  fn neighbors(
+    &self,
+    point: Point,
+    offsets: &'static [(isize, isize)],
+  ) -> impl Iterator<Item = Point> + '_ {
+    let origin = self.wrap(point);
+    offsets.iter().filter_map(move |(dx, dy)| {
+      let (x, y) = origin?;
+      let x = self.step(x, *dx, self.width, self.edges != Edges::Bounded)?;
+      let y = self.step(y, *dy, self.height, self.edges == Edges::Torus)?;
+      Some((x, y))
+    })
+  }
+
+  fn step(&self, from: usize, by: isize, size: usize, wraps: bool) -> Option<usize> {
+    let to = from as isize + by;
+    if wraps {
+      Some(to.rem_euclid(size as isize) as usize)
+    } else if 0 <= to && (to as usize) < size {
+      Some(to as usize)
+    } else {
+      None
+    }
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    (0..self.height).map(move |y| self.row(y))
+  }
+
+  /// The cells in column `x`, top to bottom, or None if the grid isn't that
+  /// wide.
+  pub fn column(&self, x: usize) -> Option<impl Iterator<Item = &T>> {
+    if x >= self.width {
+      return None;
+    }
+    Some(self.cells[x..].iter().step_by(self.width))
+  }
+
+  pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+    (0..self.width).filter_map(move |x| self.column(x))
+  }
+
+  /// Every cell and its point, row by row.
+  pub fn cells(&self) -> impl Iterator<Item = (Point, &T)> {
+    let width = self.width;
+    self
+      .cells
+      .iter()
+      .enumerate()
+      .map(move |(idx, cell)| ((idx % width, idx / width), cell))
+  }
+}
+
+/// Reads a map the way `Grid::parse` does, but hands each row to `row` as
+/// it's read instead of keeping them, for callers that store cells their own
+/// way. Gives the map's width and height.
+pub fn parse_rows<T>(
+  input: &str,
+  mut cell: impl FnMut(char) -> Option<T>,
+  mut row: impl FnMut(&[T]),
+) -> Result<(usize, usize), ParseError> {
+  let mut cells = Vec::new();
+  let mut width = None;
+  let mut height = 0;
+  for (idx, line) in input.lines().enumerate() {
+    let line = line.trim_end();
+    if line.is_empty() {
+      continue;
+    }
+    cells.clear();
+    for (column, c) in line.chars().enumerate() {
+      let value = cell(c).ok_or_else(|| {
+        ParseError::new(idx + 1, column + 1, format!("unknown map square: {:?}", c))
+      })?;
+      cells.push(value);
+    }
+    let width = *width.get_or_insert(cells.len());
+    if cells.len() != width {
+      return Err(ParseError::new(
+        idx + 1,
+        1,
+        format!(
+          "expected a row {} squares wide, like the first, got {}",
+          width,
+          cells.len()
+        ),
+      ));
+    }
+    row(&cells);
+    height += 1;
+  }
+  Ok((width.unwrap_or(0), height))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn digits(edges: Edges) -> Grid<u32> {
+    Grid::parse("123\n456\n", edges, |c| c.to_digit(10)).unwrap()
+  }
+
+  #[test]
+  fn parses() {
+    let grid = digits(Edges::Bounded);
+    assert_eq!((3, 2), (grid.width(), grid.height()));
+    assert_eq!(Some(&6), grid.get((2, 1)));
+    let err = Grid::parse("12\n3x\n", Edges::Bounded, |c| c.to_digit(10)).unwrap_err();
+    assert_eq!((2, 2), (err.line, err.column));
+    let err = Grid::parse("12\n\n345\n", Edges::Bounded, |c| c.to_digit(10)).unwrap_err();
+    assert_eq!((3, 1), (err.line, err.column));
+    let empty = Grid::parse("", Edges::Torus, |c| c.to_digit(10)).unwrap();
+    assert_eq!(None, empty.get((0, 0)));
+  }
+
+  #[test]
+  fn edges() {
+    let bounded = digits(Edges::Bounded);
+    assert_eq!(None, bounded.get((3, 0)));
+    let wrapped = digits(Edges::WrapHorizontal);
+    assert_eq!(Some(&1), wrapped.get((3, 0)));
+    assert_eq!(Some(&5), wrapped.get((301, 1)));
+    assert_eq!(None, wrapped.get((0, 2)));
+    let torus = digits(Edges::Torus);
+    assert_eq!(Some(&4), torus.get((3, 3)));
+  }
+
+  #[test]
+  fn neighbors() {
+    let neighbors4 = |grid: &Grid<u32>, point| -> Vec<u32> {
+      grid
+        .neighbors4(point)
+        .map(|p| *grid.get(p).unwrap())
+        .collect()
+    };
+    assert_eq!(vec![2, 4], neighbors4(&digits(Edges::Bounded), (0, 0)));
+    assert_eq!(
+      vec![3, 2, 4],
+      neighbors4(&digits(Edges::WrapHorizontal), (0, 0))
+    );
+    assert_eq!(vec![4, 3, 2, 4], neighbors4(&digits(Edges::Torus), (0, 0)));
+
+    let grid = digits(Edges::Bounded);
+    let neighbors8: Vec<u32> = grid
+      .neighbors8((1, 0))
+      .map(|p| *grid.get(p).unwrap())
+      .collect();
+    assert_eq!(vec![1, 3, 4, 5, 6], neighbors8);
+    assert_eq!(0, grid.neighbors8((5, 5)).count());
+  }
+
+  #[test]
+  fn rows_and_columns() {
+    let grid = digits(Edges::Bounded);
+    let rows: Vec<&[u32]> = grid.rows().collect();
+    assert_eq!(vec![&[1, 2, 3][..], &[4, 5, 6][..]], rows);
+    let columns: Vec<Vec<u32>> = grid
+      .columns()
+      .map(|column| column.copied().collect())
+      .collect();
+    assert_eq!(vec![vec![1, 4], vec![2, 5], vec![3, 6]], columns);
+    assert!(grid.column(3).is_none());
+    assert!(grid.column(7).is_none());
+    assert_eq!(Some(((1, 1), &5)), grid.cells().nth(4));
+  }
+}
@@ -0,0 +1,93 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/// Names a directory to look for personal puzzle inputs in before the
+/// defaults.
+pub const INPUTS_DIR_VAR: &str = "ADVENT_2020_INPUTS";
+
+/// Where to find personal puzzle inputs, which aren't checked in. Each day's
+/// input is a file named like `day_9_input.txt`.
+#[derive(Clone, Debug)]
+pub struct Inputs {
+  dirs: Vec<PathBuf>,
+}
+impl Inputs {
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self {
+      dirs: vec![dir.into()],
+    }
+  }
+
+  /// Looks in `$ADVENT_2020_INPUTS` if it's set, then `inputs/` under the
+  /// current directory, then this crate's `src/`, where inputs have
+  /// historically been kept.
+  pub fn from_env() -> Self {
+    let mut dirs = Vec::new();
+    if let Some(dir) = env::var_os(INPUTS_DIR_VAR) {
+      dirs.push(PathBuf::from(dir));
+    }
+    dirs.push(PathBuf::from("inputs"));
+    dirs.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+    Self { dirs }
+  }
+
+  pub fn path(&self, day: u8) -> Option<PathBuf> {
+    self
+      .dirs
+      .iter()
+      .map(|dir| dir.join(file_name(day)))
+      .find(|path| path.is_file())
+  }
+
+  pub fn load(&self, day: u8) -> io::Result<String> {
+    match self.path(day) {
+      Some(path) => fs::read_to_string(path),
+      None => {
+        let searched: Vec<_> = self.dirs.iter().map(|d| d.display().to_string()).collect();
+        Err(io::Error::new(
+          ErrorKind::NotFound,
+          format!("no {} in any of: {}", file_name(day), searched.join(", ")),
+        ))
+      }
+    }
+  }
+}
+
+pub fn file_name(day: u8) -> String {
+  format!("day_{}_input.txt", day)
+}
+
+/// Loads a day's personal input from the default locations.
+pub fn load(day: u8) -> io::Result<String> {
+  Inputs::from_env().load(day)
+}
+
+/// Loads a day's personal input for a test, or skips the rest of the test if
+/// there isn't one, so a fresh checkout can still run the examples.
+#[cfg(test)]
+macro_rules! my_input {
+  ($day:expr) => {
+    match crate::input::load($day) {
+      Ok(input) => input,
+      Err(e) => {
+        eprintln!("skipping: {}", e);
+        return;
+      }
+    }
+  };
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn missing_input() {
+    let inputs = Inputs::new("no/such/dir");
+    assert_eq!(None, inputs.path(1));
+    let err = inputs.load(1).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+    assert!(err.to_string().contains("day_1_input.txt"));
+  }
+}
@@ -1,6 +1,9 @@
 #[macro_use]
 extern crate lazy_static;
 
+#[macro_use]
+pub mod input;
+
 pub mod day_1;
 pub mod day_2;
 pub mod day_3;
@@ -11,3 +14,49 @@ pub mod day_7;
 pub mod day_8;
 pub mod day_9;
 pub mod day_10;
+pub mod generate;
+pub mod grid;
+pub mod parse;
+pub mod solution;
+
+pub use solution::{Args, Example, FromArgs, Puzzle, Solution};
+
+/// Every puzzle we've solved, in day and part order. Alternative
+/// implementations of a part follow its default one.
+pub const PUZZLES: &[Puzzle] = &[
+  Puzzle::of::<day_1::Part1>(),
+  Puzzle::of::<day_1::Part2>(),
+  Puzzle::of::<day_2::Part1>(),
+  Puzzle::of::<day_2::Part2>(),
+  Puzzle::of::<day_3::Part1>(),
+  Puzzle::of::<day_3::Part1BitPacked>(),
+  Puzzle::of::<day_3::Part2>(),
+  Puzzle::of::<day_3::Part2BitPacked>(),
+  Puzzle::of::<day_4::Part1>(),
+  Puzzle::of::<day_4::Part2>(),
+  Puzzle::of::<day_4::Part2Streaming>(),
+  Puzzle::of::<day_5::Part1>(),
+  Puzzle::of::<day_5::Part2>(),
+  Puzzle::of::<day_6::Part1>(),
+  Puzzle::of::<day_6::Part2>(),
+  Puzzle::of::<day_7::Part1>(),
+  Puzzle::of::<day_7::Part2>(),
+  Puzzle::of::<day_8::Part1>(),
+  Puzzle::of::<day_8::Part2>(),
+  Puzzle::of::<day_9::Part1>(),
+  Puzzle::of::<day_9::Part2>(),
+  Puzzle::of::<day_9::Part2SlidingWindow>(),
+  Puzzle::of::<day_10::Part1>(),
+  Puzzle::of::<day_10::Part2>(),
+];
+
+/// The default implementation of a day's part.
+pub fn find(day: u8, part: u8) -> Option<&'static Puzzle> {
+  implementations(day, part).next()
+}
+
+pub fn implementations(day: u8, part: u8) -> impl Iterator<Item = &'static Puzzle> {
+  PUZZLES
+    .iter()
+    .filter(move |p| p.day == day && p.part == part)
+}
@@ -0,0 +1,190 @@
+use std::fmt::{self, Display};
+use std::io;
+use std::str::FromStr;
+
+/// Something wrong with a puzzle's input, and where it is.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+  /// 1-based line number.
+  pub line: usize,
+  /// 1-based column, counted in chars.
+  pub column: usize,
+  pub message: String,
+}
+impl ParseError {
+  pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+    Self {
+      line,
+      column,
+      message: message.into(),
+    }
+  }
+
+  /// An error at `column` of a single line of text. Callers that know where
+  /// that text sits in the whole input move it there with `offset`.
+  pub fn at(column: usize, message: impl Into<String>) -> Self {
+    Self::new(1, column, message)
+  }
+
+  /// Moves an error found in a piece of the input to its position in the
+  /// whole input, where the piece starts `lines` lines and `columns`
+  /// columns in.
+  pub fn offset(mut self, lines: usize, columns: usize) -> Self {
+    if self.line == 1 {
+      self.column += columns;
+    }
+    self.line += lines;
+    self
+  }
+}
+impl Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "line {}, column {}: {}",
+      self.line, self.column, self.message
+    )
+  }
+}
+impl std::error::Error for ParseError {}
+
+/// Why input read from a stream couldn't be used: either reading it failed,
+/// or what was read didn't parse.
+#[derive(Debug)]
+pub enum ReadError {
+  Io(io::Error),
+  Parse(ParseError),
+}
+impl Display for ReadError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ReadError::Io(e) => write!(f, "couldn't read input: {}", e),
+      ReadError::Parse(e) => e.fmt(f),
+    }
+  }
+}
+impl std::error::Error for ReadError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      ReadError::Io(e) => Some(e),
+      ReadError::Parse(e) => Some(e),
+    }
+  }
+}
+impl From<io::Error> for ReadError {
+  fn from(e: io::Error) -> Self {
+    ReadError::Io(e)
+  }
+}
+impl From<ParseError> for ReadError {
+  fn from(e: ParseError) -> Self {
+    ReadError::Parse(e)
+  }
+}
+
+/// Parses every non-blank line of `input`, reporting errors on the line they
+/// came from.
+pub fn lines<'a, T>(
+  input: &'a str,
+  mut parse: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+  input
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty())
+    .map(|(idx, line)| parse(line).map_err(|e| e.offset(idx, 0)))
+    .collect()
+}
+
+/// Splits `input` into records separated by blank lines. Each record comes
+/// with the number of lines before it, for use with `ParseError::offset`.
+pub fn records(input: &str) -> Vec<(usize, &str)> {
+  let mut records = Vec::new();
+  let mut start: Option<(usize, usize)> = None;
+  let mut end = 0;
+  let mut position = 0;
+  for (idx, line) in input.split('\n').enumerate() {
+    if line.trim().is_empty() {
+      if let Some((first_line, first_byte)) = start.take() {
+        records.push((first_line, &input[first_byte..end]));
+      }
+    } else {
+      if start.is_none() {
+        start = Some((idx, position));
+      }
+      end = position + line.len();
+    }
+    position += line.len() + 1;
+  }
+  if let Some((first_line, first_byte)) = start {
+    records.push((first_line, &input[first_byte..end]));
+  }
+  records
+}
+
+/// Splits a line on whitespace, giving each word's 1-based column.
+pub fn words(line: &str) -> Vec<(usize, &str)> {
+  let mut words = Vec::new();
+  let mut start = None;
+  let ends = line
+    .char_indices()
+    .chain(std::iter::once((line.len(), ' ')));
+  for (idx, c) in ends {
+    if !c.is_whitespace() {
+      start.get_or_insert(idx);
+    } else if let Some(start) = start.take() {
+      words.push((column(line, start), &line[start..idx]));
+    }
+  }
+  words
+}
+
+/// The 1-based column of the char at `byte_offset` in `line`.
+pub fn column(line: &str, byte_offset: usize) -> usize {
+  line[..byte_offset].chars().count() + 1
+}
+
+pub fn number<T>(s: &str) -> Result<T, ParseError>
+where
+  T: FromStr,
+  T::Err: Display,
+{
+  s.trim_end()
+    .parse()
+    .map_err(|e| ParseError::at(1, format!("expected a number, got {:?}: {}", s, e)))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn errors_point_at_their_line() {
+    let result: Result<Vec<i64>, _> = lines("1\n\n2\nthree\n", number);
+    assert_eq!(4, result.unwrap_err().line);
+  }
+
+  #[test]
+  fn offsets() {
+    let err = ParseError::at(3, "oops").offset(2, 10);
+    assert_eq!((3, 13), (err.line, err.column));
+    let err = ParseError::new(2, 3, "oops").offset(2, 10);
+    assert_eq!((4, 3), (err.line, err.column));
+  }
+
+  #[test]
+  fn splits_records() {
+    assert_eq!(
+      vec![(1, "a b\nc"), (5, "d")],
+      records("\na b\nc\n\n  \nd\n\n")
+    );
+  }
+
+  #[test]
+  fn word_columns() {
+    assert_eq!(
+      vec![(1, "ab"), (5, "cd"), (8, "é"), (10, "ef")],
+      words("ab  cd é\tef")
+    );
+  }
+}
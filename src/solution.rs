@@ -0,0 +1,187 @@
+use crate::parse::ParseError;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+/// Named arguments for puzzles that need more than their input text, like
+/// day 9's preamble length. Values stay strings until a solution asks for
+/// them, so callers don't need to know each day's parameter types.
+#[derive(Clone, Default, Debug)]
+pub struct Args {
+  values: BTreeMap<String, String>,
+}
+impl Args {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with(mut self, name: &str, value: &str) -> Self {
+    self.set(name, value);
+    self
+  }
+
+  pub fn set(&mut self, name: &str, value: &str) {
+    self.values.insert(name.to_string(), value.to_string());
+  }
+
+  pub fn names(&self) -> impl Iterator<Item = &str> {
+    self.values.keys().map(|k| k.as_str())
+  }
+
+  pub fn get<T>(&self, name: &str) -> Result<Option<T>, String>
+  where
+    T: FromStr,
+    T::Err: Display,
+  {
+    match self.values.get(name) {
+      None => Ok(None),
+      Some(value) => value
+        .parse()
+        .map(Some)
+        .map_err(|e| format!("bad value for {}: {:?}: {}", name, value, e)),
+    }
+  }
+}
+
+/// The extra inputs a `Solution` takes, built from `Args`.
+pub trait FromArgs: Sized {
+  /// The argument names `from_args` reads.
+  const NAMES: &'static [&'static str];
+
+  fn from_args(args: &Args) -> Result<Self, String>;
+}
+impl FromArgs for () {
+  const NAMES: &'static [&'static str] = &[];
+
+  fn from_args(_args: &Args) -> Result<Self, String> {
+    Ok(())
+  }
+}
+
+/// Puzzle text, along with any arguments needed to run a puzzle on it.
+#[derive(Clone, Copy, Debug)]
+pub struct Example {
+  pub text: &'static str,
+  pub args: &'static [(&'static str, &'static str)],
+}
+impl Example {
+  pub const fn new(text: &'static str) -> Self {
+    Self { text, args: &[] }
+  }
+
+  pub fn args(&self) -> Args {
+    let mut args = Args::new();
+    for (name, value) in self.args {
+      args.set(name, value);
+    }
+    args
+  }
+}
+
+/// One part of one day's puzzle.
+pub trait Solution {
+  /// Everything the solver takes besides the puzzle text. `()` for most days.
+  type Input: FromArgs;
+  type Answer: Debug;
+
+  const DAY: u8;
+  const PART: u8;
+  /// Tells apart alternative implementations of the same part.
+  const NAME: &'static str = "default";
+  /// The worked example from the puzzle's description.
+  const EXAMPLE: Option<Example> = None;
+
+  fn solve(text: &str, input: Self::Input) -> Result<Self::Answer, ParseError>;
+}
+
+/// A `Solution` with its types erased, so that every day and part can be
+/// listed together and run by number.
+#[derive(Clone, Copy)]
+pub struct Puzzle {
+  pub day: u8,
+  pub part: u8,
+  pub name: &'static str,
+  pub params: &'static [&'static str],
+  pub example: Option<Example>,
+  run: fn(&str, &Args) -> Result<String, String>,
+}
+impl Puzzle {
+  pub const fn of<S: Solution>() -> Self {
+    Self {
+      day: S::DAY,
+      part: S::PART,
+      name: S::NAME,
+      params: S::Input::NAMES,
+      example: S::EXAMPLE,
+      run: run::<S>,
+    }
+  }
+
+  pub fn run(&self, text: &str, args: &Args) -> Result<String, String> {
+    (self.run)(text, args)
+  }
+}
+
+fn run<S: Solution>(text: &str, args: &Args) -> Result<String, String> {
+  let input = S::Input::from_args(args)?;
+  let answer = S::solve(text, input).map_err(|e| e.to_string())?;
+  Ok(format!("{:?}", answer))
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{find, implementations, PUZZLES};
+
+  use super::*;
+
+  #[test]
+  fn registry_covers_every_day() {
+    let mut listed: Vec<_> = PUZZLES.iter().map(|p| (p.day, p.part)).collect();
+    listed.dedup();
+    let expected: Vec<_> = (1..=10).flat_map(|day| vec![(day, 1), (day, 2)]).collect();
+    assert_eq!(expected, listed);
+  }
+
+  #[test]
+  fn alternative_implementations() {
+    let names: Vec<_> = implementations(9, 2).map(|p| p.name).collect();
+    assert_eq!(vec!["default", "sliding window"], names);
+    assert_eq!("default", find(9, 2).unwrap().name);
+  }
+
+  #[test]
+  fn examples_run() {
+    for puzzle in PUZZLES {
+      if let Some(example) = puzzle.example {
+        let result = puzzle.run(example.text, &example.args());
+        assert!(
+          result.is_ok(),
+          "day {} part {}: {:?}",
+          puzzle.day,
+          puzzle.part,
+          result
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn run_by_number() {
+    let puzzle = find(1, 1).unwrap();
+    assert_eq!(
+      Ok("Some(514579)".to_string()),
+      puzzle.run("1721\n979\n366\n299\n675\n1456\n", &Args::new())
+    );
+  }
+
+  #[test]
+  fn run_with_args() {
+    let puzzle = find(9, 1).unwrap();
+    assert_eq!(&["window-size"], puzzle.params);
+    let input = "1\n2\n3\n4\n5\n7\n100\n";
+    let args = Args::new().with("window-size", "3");
+    assert_eq!(Ok("Some(100)".to_string()), puzzle.run(input, &args));
+    let args = Args::new().with("window-size", "three");
+    assert!(puzzle.run(input, &args).is_err());
+  }
+}